@@ -0,0 +1,90 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use oxygin::{
+    components::{Edible, Gravity, Position, Velocity},
+    ecs::{ArchetypeStorage, Query, ECS},
+};
+
+const COUNTS: [usize; 3] = [1_000, 10_000, 100_000];
+
+// Food has position, velocity and gravity, every tenth entity is edible too so there is
+// more than one archetype to walk
+fn secondary_map_world(count: usize) -> ECS<'static> {
+    let mut ecs = ECS::new();
+    ecs.register_component::<Position>();
+    ecs.register_component::<Velocity>();
+    ecs.register_component::<Gravity>();
+    ecs.register_component::<Edible>();
+    for i in 0..count {
+        let entity = ecs.create_entity();
+        ecs.add_component(entity, Position::new(0, 0)).unwrap();
+        ecs.add_component(entity, Velocity::new(1, 1)).unwrap();
+        ecs.add_component(entity, Gravity::new(0.0, 0.981)).unwrap();
+        if i % 10 == 0 {
+            ecs.add_component(entity, Edible { calories: 1 }).unwrap();
+        }
+    }
+    ecs
+}
+
+fn archetype_world(count: usize) -> ArchetypeStorage {
+    let mut storage = ArchetypeStorage::new();
+    for i in 0..count {
+        let entity = storage.create();
+        storage.insert(entity, Position::new(0, 0));
+        storage.insert(entity, Velocity::new(1, 1));
+        storage.insert(entity, Gravity::new(0.0, 0.981));
+        if i % 10 == 0 {
+            storage.insert(entity, Edible { calories: 1 });
+        }
+    }
+    storage
+}
+
+fn iterate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("iterate position velocity gravity");
+    for count in COUNTS {
+        let mut ecs = secondary_map_world(count);
+        group.bench_with_input(BenchmarkId::new("secondary map", count), &count, |b, _| {
+            b.iter(|| {
+                let mut query = Query::<(&mut Position, &mut Velocity, &Gravity)>::new(&mut ecs);
+                for (_, (mut position, mut velocity, gravity)) in query.iter_mut() {
+                    velocity.vy += gravity.gy as i32;
+                    position.x += velocity.vx;
+                    position.y += velocity.vy;
+                }
+            })
+        });
+
+        let mut storage = archetype_world(count);
+        group.bench_with_input(BenchmarkId::new("archetype", count), &count, |b, _| {
+            b.iter(|| {
+                let query = storage.query::<(&mut Position, &mut Velocity, &Gravity)>();
+                for (_, (position, velocity, gravity)) in query {
+                    velocity.vy += gravity.gy as i32;
+                    position.x += velocity.vx;
+                    position.y += velocity.vy;
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn spawn(c: &mut Criterion) {
+    let mut group = c.benchmark_group("spawn food");
+    group.sample_size(20);
+    for count in COUNTS {
+        group.bench_with_input(
+            BenchmarkId::new("secondary map", count),
+            &count,
+            |b, count| b.iter(|| black_box(secondary_map_world(*count))),
+        );
+        group.bench_with_input(BenchmarkId::new("archetype", count), &count, |b, count| {
+            b.iter(|| black_box(archetype_world(*count)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, iterate, spawn);
+criterion_main!(benches);
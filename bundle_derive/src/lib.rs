@@ -1,60 +1,162 @@
 extern crate proc_macro;
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, Data, DeriveInput};
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Field, Index, Member};
 
-#[proc_macro_derive(Bundle)]
+// How a single field takes part in the bundle
+enum FieldKind {
+    Component,
+    // `#[bundle]`, another bundle whose components are spread into this one
+    Bundle,
+    // `#[bundle(skip)]`, dropped when the bundle is spawned
+    Skip,
+}
+
+fn field_kind(field: &Field) -> syn::Result<FieldKind> {
+    let mut kind = FieldKind::Component;
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("bundle"))
+    {
+        if matches!(attr.meta, syn::Meta::Path(_)) {
+            kind = FieldKind::Bundle;
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                kind = FieldKind::Skip;
+                Ok(())
+            } else {
+                Err(meta.error("expected `#[bundle]` or `#[bundle(skip)]`"))
+            }
+        })?;
+    }
+    Ok(kind)
+}
+
+/// Every field is a component unless marked `#[bundle]`, for a nested bundle, or
+/// `#[bundle(skip)]`. Works for named and tuple structs, generic ones included
+#[proc_macro_derive(Bundle, attributes(bundle))]
 pub fn derive_bundle(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
-    let name = input.ident;
+    match expand(input) {
+        Ok(expanded) => expanded.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
 
-    let register_impl = match input.data {
-        Data::Struct(ref data) => {
-            let component_registers = data.fields.iter().map(|f| {
-                let ty = &f.ty;
-                quote! {
-                    store.try_register::<#ty>();
-                }
-            });
-            quote! {
-                fn register(store: &mut ComponentStorage) {
-                    #(#component_registers)*
-                }
-            }
+fn expand(mut input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = input.ident.clone();
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        Data::Enum(data) => {
+            return Err(syn::Error::new_spanned(
+                data.enum_token,
+                "Bundle can only be derived for structs, not enums",
+            ))
+        }
+        Data::Union(data) => {
+            return Err(syn::Error::new_spanned(
+                data.union_token,
+                "Bundle can only be derived for structs, not unions",
+            ))
         }
-        _ => unimplemented!(),
     };
 
-    let add_data_impl = match input.data {
-        Data::Struct(ref data) => {
-            let inserts = data.fields.iter().map(|f| {
-                let name = &f.ident;
-                let ty = &f.ty;
-                quote! {
-                    store.get_mut::<#ty>().unwrap().insert(entity, self.#name);
-                }
-            });
-            quote! {
-                fn add_data(self, store: &mut ComponentStorage, bundles: &Bundles) -> Option<Entity> {
-                    if bundles.get(&TypeId::of::<Self>()).is_none() {
-                        return None;
-                    }
-
-                    let entity = store.create();
-                    #(#inserts)*
-                    Some(entity)
-                }
+    let mut registers = Vec::new();
+    let mut inserts = Vec::new();
+    let mut required = Vec::new();
+    let mut removes = Vec::new();
+    let mut bounds: Vec<syn::WherePredicate> = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let ty = &field.ty;
+        let member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(i)),
+        };
+        match field_kind(field)? {
+            FieldKind::Component => {
+                registers.push(quote! {
+                    store.try_register::<#ty>();
+                });
+                inserts.push(quote! {
+                    store.insert_without_required::<#ty>(entity, self.#member)?;
+                });
+                required.push(quote! {
+                    store.insert_required::<#ty>(entity)?;
+                });
+                removes.push(quote! {
+                    store.discard::<#ty>(entity);
+                });
+                bounds.push(parse_quote!(#ty: ::oxygin::ecs::Component + 'static));
+            }
+            FieldKind::Bundle => {
+                registers.push(quote! {
+                    <#ty as ::oxygin::ecs::Bundle>::register(store);
+                });
+                inserts.push(quote! {
+                    ::oxygin::ecs::Bundle::insert_components(self.#member, store, entity)?;
+                });
+                required.push(quote! {
+                    <#ty as ::oxygin::ecs::Bundle>::insert_required(store, entity)?;
+                });
+                removes.push(quote! {
+                    <#ty as ::oxygin::ecs::Bundle>::remove_from(store, entity);
+                });
+                bounds.push(parse_quote!(#ty: ::oxygin::ecs::Bundle));
             }
+            FieldKind::Skip => {}
         }
-        _ => unimplemented!(),
-    };
+    }
 
-    let expanded = quote! {
-        impl Bundle for #name {
-            #register_impl
-            #add_data_impl
-        }
-    };
+    if inserts.is_empty() {
+        inserts.push(quote! {
+            let _ = (store, entity);
+        });
+        required.push(quote! {
+            let _ = (store, entity);
+        });
+        removes.push(quote! {
+            let _ = (store, entity);
+        });
+    }
+
+    // Generic fields get their bounds from how they're used, so `T` only has to be a
+    // component where it's spawned
+    input.generics.make_where_clause().predicates.extend(bounds);
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::oxygin::ecs::Bundle for #name #ty_generics #where_clause {
+            fn register(store: &mut ::oxygin::ecs::ComponentStorage) {
+                #(#registers)*
+            }
 
-    TokenStream::from(expanded)
+            fn insert_components(
+                self,
+                store: &mut ::oxygin::ecs::ComponentStorage,
+                entity: ::oxygin::ecs::Entity,
+            ) -> ::std::result::Result<(), ::oxygin::ecs::EcsError> {
+                #(#inserts)*
+                ::std::result::Result::Ok(())
+            }
+
+            // After every field is in, so required defaults only fill real gaps
+            fn insert_required(
+                store: &mut ::oxygin::ecs::ComponentStorage,
+                entity: ::oxygin::ecs::Entity,
+            ) -> ::std::result::Result<(), ::oxygin::ecs::EcsError> {
+                #(#required)*
+                ::std::result::Result::Ok(())
+            }
+
+            fn remove_from(
+                store: &mut ::oxygin::ecs::ComponentStorage,
+                entity: ::oxygin::ecs::Entity,
+            ) {
+                #(#removes)*
+            }
+        }
+    })
 }
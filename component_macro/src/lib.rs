@@ -2,35 +2,106 @@ extern crate proc_macro;
 
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, Data, DeriveInput, Fields};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    punctuated::Punctuated,
+    DeriveInput, Expr, LitStr, Path, Token,
+};
 
-#[proc_macro_derive(ComponentBundle)]
-pub fn component_bundle_derive(input: TokenStream) -> TokenStream {
+// One entry in `require(..)`, `Velocity` or `Velocity = Velocity::new(0, 0)`
+struct Required {
+    path: Path,
+    constructor: Option<Expr>,
+}
+
+impl Parse for Required {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = input.parse()?;
+        let constructor = if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        Ok(Self { path, constructor })
+    }
+}
+
+/// `#[component(storage = "sparse")]` picks the column type, `dense` is the default.
+/// `#[component(require(Position, Velocity = Velocity::new(0, 0)))]` adds each to any
+/// entity that gets this component without them, from `Default` unless an expression to
+/// build it is given
+#[proc_macro_derive(Component, attributes(component))]
+pub fn derive_component(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
-    let name = input.ident;
-
-    let fields = match input.data {
-        Data::Struct(data_struct) => match data_struct.fields {
-            Fields::Named(fields_named) => fields_named.named,
-            _ => panic!("ComponentBundle can only be derived for structs with named fields"),
-        },
-        _ => panic!("ComponentBundle can only be derived for structs"),
-    };
-
-    let field_names = fields.iter().map(|f| &f.ident);
-
-    let gen = quote! {
-        impl Bundle for #name {
-            type Components = std::vec::IntoIter<Box<dyn Component>>;
-
-            fn components(&self) -> Self::Components {
-                let mut components: Vec<Box<dyn Component>> = Vec::new();
-                #(
-                    components.push(Box::new(self.#field_names.clone()) as Box<dyn Component>);
-                )*
-                components.into_iter()
+    match expand(input) {
+        Ok(expanded) => expanded.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut storage = None;
+    let mut required: Vec<Required> = Vec::new();
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("component"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("storage") {
+                let value: LitStr = meta.value()?.parse()?;
+                storage = Some(match value.value().as_str() {
+                    "dense" => quote! { ::oxygin::ecs::StorageKind::Dense },
+                    "sparse" => quote! { ::oxygin::ecs::StorageKind::Sparse },
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            value,
+                            "storage must be \"dense\" or \"sparse\"",
+                        ))
+                    }
+                });
+                Ok(())
+            } else if meta.path.is_ident("require") {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                required.extend(Punctuated::<Required, Token![,]>::parse_terminated(
+                    &content,
+                )?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `storage = \"..\"` or `require(..)`"))
+            }
+        })?;
+    }
+
+    let storage = storage.map(|storage| {
+        quote! {
+            const STORAGE: ::oxygin::ecs::StorageKind = #storage;
+        }
+    });
+    let adds = required
+        .iter()
+        .map(|Required { path, constructor }| match constructor {
+            Some(constructor) => quote! { required.add_with::<#path>(|| #constructor); },
+            None => quote! { required.add::<#path>(); },
+        });
+    let required_components = (!required.is_empty()).then(|| {
+        quote! {
+            fn required_components(required: &mut ::oxygin::ecs::RequiredComponents) {
+                #(#adds)*
             }
         }
-    };
-    gen.into()
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::oxygin::ecs::Component for #name #ty_generics #where_clause {
+            #storage
+            #required_components
+        }
+    })
 }
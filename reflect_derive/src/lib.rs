@@ -0,0 +1,81 @@
+extern crate proc_macro;
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Index, Member};
+
+#[proc_macro_derive(Reflect)]
+pub fn derive_reflect(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match input.data {
+        Data::Struct(ref data) => &data.fields,
+        _ => {
+            return syn::Error::new_spanned(name, "Reflect can only be derived for structs")
+                .to_compile_error()
+                .into()
+        }
+    };
+
+    // Named fields are found by name, tuple fields by their index, "0", "1" and so on
+    let members: Vec<(Member, String)> = fields
+        .iter()
+        .enumerate()
+        .map(|(i, f)| match &f.ident {
+            Some(ident) => (Member::Named(ident.clone()), ident.to_string()),
+            None => (Member::Unnamed(Index::from(i)), i.to_string()),
+        })
+        .collect();
+    let field_infos = fields.iter().zip(&members).map(|(f, (_, field_name))| {
+        let ty = &f.ty;
+        quote! {
+            ::oxygin::ecs::FieldInfo::new::<#ty>(#field_name)
+        }
+    });
+    let field_arms = members.iter().map(|(member, field_name)| {
+        quote! {
+            #field_name => Some(&self.#member),
+        }
+    });
+    let field_mut_arms = members.iter().map(|(member, field_name)| {
+        quote! {
+            #field_name => Some(&mut self.#member),
+        }
+    });
+
+    let expanded = quote! {
+        impl #impl_generics ::oxygin::ecs::Reflect for #name #ty_generics #where_clause {
+            fn type_info() -> ::oxygin::ecs::TypeInfo
+            where
+                Self: Sized,
+            {
+                ::oxygin::ecs::TypeInfo::new::<Self>(vec![#(#field_infos),*])
+            }
+
+            fn as_any(&self) -> &dyn ::std::any::Any {
+                self
+            }
+
+            fn as_any_mut(&mut self) -> &mut dyn ::std::any::Any {
+                self
+            }
+
+            fn field(&self, name: &str) -> Option<&dyn ::oxygin::ecs::Reflect> {
+                match name {
+                    #(#field_arms)*
+                    _ => None,
+                }
+            }
+
+            fn field_mut(&mut self, name: &str) -> Option<&mut dyn ::oxygin::ecs::Reflect> {
+                match name {
+                    #(#field_mut_arms)*
+                    _ => None,
+                }
+            }
+        }
+    };
+
+    TokenStream::from(expanded)
+}
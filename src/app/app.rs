@@ -1,22 +1,27 @@
 use std::{
-    any::TypeId,
+    any::{type_name, TypeId},
+    collections::HashMap,
+    fs,
+    path::Path,
     sync::Arc,
     time::{Duration, Instant},
 };
 
 use winit::{
-    dpi::LogicalSize,
+    dpi::{LogicalSize, PhysicalSize},
     event_loop::{ActiveEventLoop, EventLoop},
     window::Window,
 };
 
 use crate::{
-    ecs::{Component, ECS},
+    components::GlobalTransform,
+    ecs::{Entity, Events, IntoSystemConfig, Scene, Schedule, ScheduleError, Stage, ECS},
     render::{
-        asset_manager::{AssetManager, Model},
+        asset_manager::{add_render_hooks, AssetManager, Model},
         RenderState,
     },
-    systems::{move_system, spawn_edible},
+    resources::KeyboardInput,
+    systems::propagate_transforms,
 };
 
 use super::app_state::AppState;
@@ -37,51 +42,20 @@ impl Default for Config {
     }
 }
 
-trait System<In, Out> {
-    // fn call(input: Query<T>) -> Out;
-}
-
-pub struct Query<'world, 'query> {
-    ecs: &'query ECS<'world>,
-}
-
-impl<'w, 'q> Query<'w, 'q> {
-    pub fn new(ecs: &'w ECS) -> Self {
-        Self { ecs }
-    }
-
-    pub fn get<T>(self) -> impl QueryData
-    where
-        T: QueryData,
-    {
-        self.ecs.get_component::<T>().unwrap()
-    }
-}
-
-// trait Query<T>
-// where
-//     T: QueryData,
-// {
-//     fn fetch_data(ecs: ECS) -> T;
-// }
-
-// Vec of System -> loop through -> fetch queryData -> call function -> pass query data to function
-// -> run the function
-//
-
-trait QueryData {}
-
-// impl<T: QueryData> Component for &T {}
-
-impl<T: Component> QueryData for &T {}
-
 pub struct Scheduler {
     pub ticks_per_second: u64,
     pub tick_duration: Duration,
     pub dt: Duration,
     pub last_tick_time: Instant,
     pub accumulated_time: Duration,
-    // pub systems: Vec<Into<System>>,
+    pub schedule: Schedule,
+    pub started: bool,
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Scheduler {
@@ -93,30 +67,47 @@ impl Scheduler {
             last_tick_time: Instant::now(),
             accumulated_time: Duration::ZERO,
             dt: Duration::ZERO,
+            schedule: Schedule::new(),
+            started: false,
         }
     }
-    pub fn tick<T>(&mut self, mut update: T)
-    where
-        T: FnMut(),
-    {
+
+    pub fn add_system<M>(&mut self, stage: Stage, ecs: &mut ECS, system: impl IntoSystemConfig<M>) {
+        self.schedule.add_system(stage, ecs, system);
+    }
+
+    /// Runs one frame: `Startup` the first time round, then every stage in order with
+    /// `FixedUpdate` repeated for each tick that has built up
+    pub fn tick(&mut self, ecs: &mut ECS) -> Result<(), ScheduleError> {
+        if !self.started {
+            self.schedule.run_stage(Stage::Startup, ecs)?;
+            self.started = true;
+        }
+
+        self.schedule.run_stage(Stage::PreUpdate, ecs)?;
+
         let now = Instant::now();
         self.dt = now - self.last_tick_time;
         self.last_tick_time = now;
         self.accumulated_time += self.dt;
         while self.accumulated_time >= self.tick_duration {
-            update();
+            self.schedule.run_stage(Stage::FixedUpdate, ecs)?;
+            ecs.clear_trackers();
             self.accumulated_time -= self.tick_duration;
         }
+
+        self.schedule.run_stage(Stage::Update, ecs)?;
+        self.schedule.run_stage(Stage::PostUpdate, ecs)?;
+        self.schedule.run_stage(Stage::Render, ecs)
     }
 }
 
+/// The window and `RenderState` are non-send resources in `ecs`, so systems can reach them
+/// with `NonSend` and `NonSendMut`
 pub struct App<'a> {
-    pub window: Arc<Window>,
     pub config: Config,
     pub ecs: ECS<'a>,
-    pub assets: AssetManager,
     pub runner: Scheduler,
-    pub render_state: RenderState<'a>,
 }
 
 impl<'a> App<'a> {
@@ -130,20 +121,53 @@ impl<'a> App<'a> {
                 )
                 .unwrap(),
         );
-        let assets = AssetManager::new();
         let render_state = RenderState::new(window.clone());
+        let mut ecs = ECS::new();
+        ecs.add_non_send_resource(window);
+        ecs.add_non_send_resource(render_state);
+        ecs.register_component::<GlobalTransform>();
+        ecs.add_resource(AssetManager::new());
+        ecs.init_resource::<KeyboardInput>();
+        add_render_hooks(&mut ecs);
+        // After the game systems have moved things, before the frame is drawn
+        let mut runner = Scheduler::new();
+        runner.add_system(Stage::PostUpdate, &mut ecs, propagate_transforms);
         Self {
-            window,
-            ecs: ECS::new(),
-            runner: Scheduler::new(),
-            render_state,
-            assets,
-            config: Config::default(),
+            config,
+            ecs,
+            runner,
         }
     }
 
-    pub fn init(&mut self, mut init_function: impl FnMut(&mut Self)) {
-        init_function(self);
+    pub fn window(&self) -> &Arc<Window> {
+        self.ecs
+            .get_non_send_resource::<Arc<Window>>()
+            .expect("the window is added in App::new")
+    }
+
+    pub fn render_state(&mut self) -> &mut RenderState<'static> {
+        self.ecs
+            .get_mut_non_send_resource::<RenderState>()
+            .expect("the render state is added in App::new")
+    }
+
+    pub fn resize(&mut self, size: PhysicalSize<u32>) {
+        let aspect_ratio = self.config.aspect_ratio;
+        self.render_state().resize(size, aspect_ratio);
+    }
+
+    /// Draws the world, taking the render state out of it for the length of the frame
+    pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        self.ecs
+            .non_send_resource_scope(|ecs, render_state: &mut RenderState| render_state.render(ecs))
+            .expect("the render state is added in App::new")
+    }
+
+    pub fn init(
+        &mut self,
+        mut init_function: impl FnMut(&mut Self) -> eyre::Result<()>,
+    ) -> eyre::Result<()> {
+        init_function(self)
     }
 
     pub fn register_asset<T: Into<Model> + 'static>(&mut self, model: T) {
@@ -151,24 +175,59 @@ impl<'a> App<'a> {
         let mut new_model: Model = model.into();
         new_model.adjust_for_aspect_ratio(self.config.aspect_ratio);
 
-        self.render_state.register_new_buffer(10, &new_model, id);
-        self.assets.register(new_model, id);
+        self.render_state().register_new_buffer(10, &new_model, id);
+        self.ecs
+            .get_mut_resource::<AssetManager>()
+            .unwrap()
+            .register(new_model, id, type_name::<T>());
+    }
+
+    pub fn add_system<M>(&mut self, stage: Stage, system: impl IntoSystemConfig<M>) {
+        self.runner.add_system(stage, &mut self.ecs, system);
     }
 
-    pub fn add_system<T: FnMut(ECS) + 'static>(&mut self, system: T) {
-        self.runner.systems.push(Box::new(system));
+    /// Adds the `Events<T>` resource and swaps its buffers every `PreUpdate`, so an event
+    /// lives for two frames
+    pub fn add_event<T: Send + Sync + 'static>(&mut self) {
+        self.ecs.init_resource::<Events<T>>();
+        self.add_system(Stage::PreUpdate, Events::<T>::update_system);
     }
 
-    pub fn run<I: FnMut(&mut App)>(init: I) {
+    /// Writes every entity to `path`, as json if it ends in `.json` and ron otherwise
+    pub fn save_scene(&self, path: impl AsRef<Path>) -> eyre::Result<()> {
+        let path = path.as_ref();
+        let scene = Scene::from_ecs(&self.ecs)?;
+        let contents = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => scene.to_json()?,
+            _ => scene.to_ron()?,
+        };
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Spawns everything saved in `path` alongside what's already there
+    pub fn load_scene(&mut self, path: impl AsRef<Path>) -> eyre::Result<HashMap<u32, Entity>> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)?;
+        let scene = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => Scene::from_json(&contents)?,
+            _ => Scene::from_ron(&contents)?,
+        };
+        Ok(scene.spawn(&mut self.ecs)?)
+    }
+
+    pub fn run<I: FnMut(&mut App) -> eyre::Result<()>>(init: I) {
         let events: EventLoop<()> = winit::event_loop::EventLoop::new().unwrap();
         let mut state = AppState::Unitialised { init };
         events.run_app(&mut state).unwrap();
     }
 
-    pub fn update(&mut self) {
-        self.runner.tick(|| {
-            move_system(&mut self.ecs, &mut self.assets);
-            spawn_edible(&mut self.ecs, &mut self.assets);
-        });
+    pub fn update(&mut self) -> Result<(), ScheduleError> {
+        let result = self.runner.tick(&mut self.ecs);
+        // A failed command only loses that one change, the game keeps going
+        for error in self.ecs.take_command_errors() {
+            eprintln!("command failed: {}", error);
+        }
+        result
     }
 }
@@ -1,28 +1,35 @@
 use winit::{application::ApplicationHandler, event::WindowEvent, event_loop::ActiveEventLoop};
 
-use crate::systems::handle_input_system;
+use crate::resources::KeyboardInput;
 
 use super::app::App;
 
-pub enum AppState<'a, I: FnOnce(&mut App)> {
+#[allow(clippy::large_enum_variant)]
+pub enum AppState<'a, I: FnOnce(&mut App) -> eyre::Result<()>> {
     Initialised(App<'a>),
     Unitialised { init: I },
 }
 
-impl<'a, I: FnMut(&mut App)> AppState<'a, I> {
+impl<'a, I: FnMut(&mut App) -> eyre::Result<()>> AppState<'a, I> {
     pub fn init(&mut self, event_loop: &ActiveEventLoop) {
         *self = match self {
             Self::Initialised(_app) => panic!(),
             Self::Unitialised { init } => {
                 let mut app = App::new(event_loop);
-                init(&mut app);
+                if let Err(report) = init(&mut app) {
+                    eprintln!("{:?}", report);
+                    event_loop.exit();
+                } else if let Err(error) = app.runner.schedule.build() {
+                    eprintln!("{}", error);
+                    event_loop.exit();
+                }
                 Self::Initialised(app)
             }
         }
     }
 }
 
-impl<'a, I: FnMut(&mut App)> ApplicationHandler for AppState<'a, I> {
+impl<'a, I: FnMut(&mut App) -> eyre::Result<()>> ApplicationHandler for AppState<'a, I> {
     fn window_event(
         &mut self,
         event_loop: &winit::event_loop::ActiveEventLoop,
@@ -37,29 +44,33 @@ impl<'a, I: FnMut(&mut App)> ApplicationHandler for AppState<'a, I> {
             AppState::Initialised(app) => app,
         };
 
-        if app.window.id() != window_id {
+        if app.window().id() != window_id {
             return;
         }
         match event {
             WindowEvent::CloseRequested => event_loop.exit(),
-            WindowEvent::Resized(physical_size) => {
-                app.render_state
-                    .resize(physical_size, app.config.aspect_ratio);
-            }
+            WindowEvent::Resized(physical_size) => app.resize(physical_size),
 
             WindowEvent::KeyboardInput { event, .. } => {
-                handle_input_system(&event, &mut app.ecs);
+                if let Some(keyboard) = app.ecs.get_mut_resource::<KeyboardInput>() {
+                    keyboard.events.push(event);
+                }
             }
 
             WindowEvent::RedrawRequested => {
-                app.window.request_redraw();
-                app.update();
+                app.window().request_redraw();
+                if let Err(error) = app.update() {
+                    eprintln!("{}", error);
+                    event_loop.exit();
+                    return;
+                }
 
-                match app.render_state.render(&mut app.ecs, &mut app.assets) {
+                match app.render() {
                     Ok(_) => {}
-                    Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => app
-                        .render_state
-                        .resize(app.render_state.size, app.config.aspect_ratio),
+                    Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                        let size = app.render_state().size;
+                        app.resize(size);
+                    }
                     Err(wgpu::SurfaceError::OutOfMemory) => event_loop.exit(),
                     Err(wgpu::SurfaceError::Timeout) => {}
                 }
@@ -108,7 +119,7 @@ impl<'a, I: FnMut(&mut App)> ApplicationHandler for AppState<'a, I> {
     fn about_to_wait(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
         match self {
             AppState::Unitialised { .. } => self.init(event_loop),
-            AppState::Initialised(app) => app.window.request_redraw(),
+            AppState::Initialised(app) => app.window().request_redraw(),
         }
     }
 
@@ -1,2 +1,3 @@
+#[allow(clippy::module_inception)]
 pub mod app;
 pub mod app_state;
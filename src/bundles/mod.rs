@@ -1,21 +1,38 @@
-use std::any::TypeId;
-
-use bundle_derive::Bundle;
 use rand::Rng;
 
 use crate::{
     components::{Edible, Gravity, InputState, Physics, Position, Render, Size, Velocity},
-    ecs::{Bundle, Bundles, ComponentStorage, Entity},
+    ecs::Bundle,
+    render::asset_manager::{Food, Square},
 };
 
+/// Everything that falls
+#[derive(Bundle)]
+pub struct PhysicsBundle {
+    pub velocity: Velocity,
+    pub grav: Gravity,
+}
+
+impl Default for PhysicsBundle {
+    fn default() -> Self {
+        Self {
+            velocity: Velocity { vx: 0, vy: 0 },
+            grav: Gravity {
+                gx: 0.0,
+                gy: 9.81 / 10.0,
+            },
+        }
+    }
+}
+
 #[derive(Bundle)]
 pub struct PlayerBundle {
     pub position: Position,
     pub size: Size,
     pub input: InputState,
     pub physics: Physics,
-    pub velocity: Velocity,
-    pub grav: Gravity,
+    #[bundle]
+    pub physics_bundle: PhysicsBundle,
     pub render: Render,
 }
 
@@ -26,12 +43,8 @@ impl PlayerBundle {
             size: Size::new(size),
             input: InputState::new(),
             physics: Physics { speed: 0.05 },
-            velocity: Velocity { vx: 0, vy: 0 },
-            grav: Gravity {
-                gx: 0.0,
-                gy: 9.81 / 10.0,
-            },
-            render: Render::new(t, b, 0.0, scale),
+            physics_bundle: PhysicsBundle::default(),
+            render: Render::new(t, b, 0.0, scale).with_model::<Square>(),
         }
     }
 }
@@ -41,11 +54,17 @@ pub struct FoodBundle {
     pub size: Size,
     pub position: Position,
     pub edible: Edible,
-    pub velocity: Velocity,
-    pub grav: Gravity,
+    #[bundle]
+    pub physics_bundle: PhysicsBundle,
     pub render: Render,
 }
 
+impl Default for FoodBundle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl FoodBundle {
     pub fn new() -> Self {
         let x = rand::thread_rng().gen_range(-1.0..1.0);
@@ -54,16 +73,9 @@ impl FoodBundle {
         Self {
             size: Size { size: 10 },
             position: Position::random(),
-            edible: Edible {
-                eaten: false,
-                calories: 10,
-            },
-            velocity: Velocity { vx: 0, vy: 0 },
-            grav: Gravity {
-                gx: 0.0,
-                gy: 9.81 / 10.0,
-            },
-            render: Render::new(x, y, 0.0, 0.001),
+            edible: Edible { calories: 10 },
+            physics_bundle: PhysicsBundle::default(),
+            render: Render::new(x, y, 0.0, 0.001).with_model::<Food>(),
         }
     }
 }
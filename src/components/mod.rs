@@ -1,9 +1,15 @@
+use std::any::type_name;
+
 use nalgebra::{Matrix4, Vector3};
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 
-use crate::{ecs::Component, render::InstanceRaw};
+use crate::{
+    ecs::{Component, Reflect},
+    render::InstanceRaw,
+};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect, Component)]
 pub struct Position {
     pub x: i32,
     pub y: i32,
@@ -21,9 +27,9 @@ impl Position {
     }
 }
 
-impl Component for Position {}
-
-#[derive(Clone, Debug)]
+// Moving something only makes sense once it has somewhere to be
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect, Component)]
+#[component(require(Position = Position::new(0, 0)))]
 pub struct Velocity {
     pub vx: i32,
     pub vy: i32,
@@ -35,9 +41,9 @@ impl Velocity {
     }
 }
 
-impl Component for Velocity {}
-
-#[derive(Clone)]
+// Gravity works by changing the velocity
+#[derive(Clone, Serialize, Deserialize, Reflect, Component)]
+#[component(require(Velocity = Velocity::new(0, 0)))]
 pub struct Gravity {
     pub gx: f32,
     pub gy: f32,
@@ -49,9 +55,7 @@ impl Gravity {
     }
 }
 
-impl Component for Gravity {}
-
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize, Reflect, Component)]
 pub struct Size {
     pub size: u32,
 }
@@ -62,9 +66,9 @@ impl Size {
     }
 }
 
-impl Component for Size {}
-
-#[derive(Clone, Debug)]
+// Only the player has input, no point reserving a slot for every food entity
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect, Component)]
+#[component(storage = "sparse")]
 pub struct InputState {
     pub up: bool,
     pub right: bool,
@@ -73,7 +77,11 @@ pub struct InputState {
     pub space: bool,
 }
 
-impl Component for InputState {}
+impl Default for InputState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl InputState {
     pub fn new() -> Self {
@@ -87,13 +95,11 @@ impl InputState {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize, Reflect, Component)]
 pub struct Physics {
     pub speed: f32,
 }
 
-impl Component for Physics {}
-
 // #[derive(Clone)]
 // pub struct ColorComponent {
 //     pub rgb: Color,
@@ -108,18 +114,19 @@ impl Component for Physics {}
 //
 // impl Component for ColorComponent {}
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize, Reflect, Component)]
 pub struct Edible {
-    pub eaten: bool,
     pub calories: u32,
 }
 
-impl Component for Edible {}
-
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize, Reflect, Component)]
+#[component(require(GlobalTransform))]
 pub struct Render {
     pub transform: Vector3<f32>,
     pub scale: Vector3<f32>,
+    /// Type name of the model this is drawn with, nothing is drawn until one is set
+    #[serde(default)]
+    pub model: String,
 }
 
 impl Render {
@@ -127,19 +134,50 @@ impl Render {
         Self {
             transform: Vector3::new(x, y, z),
             scale: Vector3::new(scale, scale, 1.0),
+            model: String::new(),
         }
     }
 
-    pub fn to_raw(&self) -> InstanceRaw {
+    /// Draws with the model registered through `App::register_asset::<M>`
+    pub fn with_model<M: 'static>(mut self) -> Self {
+        self.model = type_name::<M>().to_string();
+        self
+    }
+
+    /// The transform relative to the parent, or to the world for a root
+    pub fn local_matrix(&self) -> Matrix4<f32> {
         let transform = Matrix4::new_translation(&self.transform);
         let scale = Matrix4::new_nonuniform_scaling(&self.scale);
+        transform * scale
+    }
+
+    pub fn to_raw(&self) -> InstanceRaw {
         InstanceRaw {
-            model: (transform * scale).into(),
+            model: self.local_matrix().into(),
         }
     }
+}
 
-    // TODO: Add functions so that whenever this is updated change the corrosponding renderable
-    // instance to re-calculate
+/// Where a `Render` ends up once every parent's transform is applied, written by
+/// `propagate_transforms` and drawn by the renderer
+#[derive(Clone, Debug, PartialEq, Reflect, Component)]
+pub struct GlobalTransform {
+    pub matrix: Matrix4<f32>,
 }
 
-impl Component for Render {}
+// Until `propagate_transforms` gets to it
+impl Default for GlobalTransform {
+    fn default() -> Self {
+        Self {
+            matrix: Matrix4::identity(),
+        }
+    }
+}
+
+impl GlobalTransform {
+    pub fn to_raw(&self) -> InstanceRaw {
+        InstanceRaw {
+            model: self.matrix.into(),
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use std::any::{type_name, TypeId};
+
+#[derive(Clone, Copy, Debug)]
+pub struct AccessId {
+    pub id: TypeId,
+    pub name: &'static str,
+}
+
+impl AccessId {
+    pub fn of<T: 'static>() -> Self {
+        Self {
+            id: TypeId::of::<T>(),
+            name: type_name::<T>(),
+        }
+    }
+}
+
+impl PartialEq for AccessId {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for AccessId {}
+
+// Which component types something reads and writes, used to reject aliasing `&mut` borrows
+#[derive(Clone, Debug, Default)]
+pub struct Access {
+    pub reads: Vec<AccessId>,
+    pub writes: Vec<AccessId>,
+}
+
+impl Access {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_read(&mut self, id: AccessId) {
+        if !self.reads.contains(&id) {
+            self.reads.push(id);
+        }
+    }
+
+    pub fn add_write(&mut self, id: AccessId) {
+        if !self.writes.contains(&id) {
+            self.writes.push(id);
+        }
+    }
+
+    /// Types written by one side and touched at all by the other
+    pub fn conflicts(&self, other: &Access) -> Vec<AccessId> {
+        let mut conflicts = Vec::new();
+        for write in &self.writes {
+            if other.reads.contains(write) || other.writes.contains(write) {
+                conflicts.push(*write);
+            }
+        }
+        for write in &other.writes {
+            if self.reads.contains(write) && !conflicts.contains(write) {
+                conflicts.push(*write);
+            }
+        }
+        conflicts
+    }
+
+    pub fn extend(&mut self, other: &Access) {
+        other.reads.iter().for_each(|read| self.add_read(*read));
+        other.writes.iter().for_each(|write| self.add_write(*write));
+    }
+}
+
+// Everything a system touches, with resources kept apart from components since the same type
+// could in theory be both
+#[derive(Clone, Debug, Default)]
+pub struct SystemAccess {
+    pub components: Access,
+    pub resources: Access,
+    pub non_send: Access,
+    pub exclusive: bool,
+    /// Uses a non-send resource, so has to run on the thread that owns the world
+    pub main_thread: bool,
+}
+
+impl SystemAccess {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn exclusive() -> Self {
+        Self {
+            exclusive: true,
+            ..Self::default()
+        }
+    }
+
+    pub fn conflicts(&self, other: &SystemAccess) -> Vec<AccessId> {
+        let mut conflicts = self.components.conflicts(&other.components);
+        conflicts.extend(self.resources.conflicts(&other.resources));
+        conflicts.extend(self.non_send.conflicts(&other.non_send));
+        conflicts
+    }
+
+    /// Main thread systems never share a batch, a batch of one runs on the calling thread
+    pub fn is_compatible(&self, other: &SystemAccess) -> bool {
+        !self.exclusive
+            && !other.exclusive
+            && !self.main_thread
+            && !other.main_thread
+            && self.conflicts(other).is_empty()
+    }
+
+    pub fn extend(&mut self, other: &SystemAccess) {
+        self.components.extend(&other.components);
+        self.resources.extend(&other.resources);
+        self.non_send.extend(&other.non_send);
+        self.exclusive |= other.exclusive;
+        self.main_thread |= other.main_thread;
+    }
+}
@@ -0,0 +1,416 @@
+use std::{
+    any::{Any, TypeId},
+    cell::UnsafeCell,
+    collections::HashMap,
+};
+
+use slotmap::SlotMap;
+
+use super::{Access, Component, Entity, QueryData};
+
+// One component type's values for every entity in an archetype, row `n` of every column
+// belongs to the same entity
+trait TableColumn: Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    /// A column of the same type with nothing in it
+    fn empty(&self) -> Box<dyn TableColumn>;
+
+    fn swap_remove(&mut self, row: usize);
+
+    /// Moves the value at `row` onto the end of `other`, which must hold the same type
+    fn move_row(&mut self, row: usize, other: &mut dyn TableColumn);
+}
+
+struct Column<T> {
+    data: UnsafeCell<Vec<T>>,
+}
+
+// SAFETY: the data is only written through `&mut self` or by a `TableQuery` that checked its
+// access up front, same as `ComponentColumn`
+unsafe impl<T: Send> Send for Column<T> {}
+unsafe impl<T: Send + Sync> Sync for Column<T> {}
+
+impl<T> Column<T> {
+    fn new() -> Self {
+        Self {
+            data: UnsafeCell::new(Vec::new()),
+        }
+    }
+}
+
+impl<T> TableColumn for Column<T>
+where
+    T: Component + 'static,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn empty(&self) -> Box<dyn TableColumn> {
+        Box::new(Column::<T>::new())
+    }
+
+    fn swap_remove(&mut self, row: usize) {
+        self.data.get_mut().swap_remove(row);
+    }
+
+    fn move_row(&mut self, row: usize, other: &mut dyn TableColumn) {
+        let value = self.data.get_mut().swap_remove(row);
+        let other = other.as_any_mut().downcast_mut::<Column<T>>().unwrap();
+        other.data.get_mut().push(value);
+    }
+}
+
+/// Every entity with exactly the same set of components, stored as one contiguous column per
+/// component type
+pub struct Archetype {
+    types: Vec<TypeId>,
+    columns: HashMap<TypeId, Box<dyn TableColumn>>,
+    entities: Vec<Entity>,
+}
+
+impl Archetype {
+    fn new(types: Vec<TypeId>, columns: HashMap<TypeId, Box<dyn TableColumn>>) -> Self {
+        Self {
+            types,
+            columns,
+            entities: Vec::new(),
+        }
+    }
+
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.columns.contains_key(&TypeId::of::<T>())
+    }
+
+    fn column<T: 'static>(&self) -> Option<&Column<T>> {
+        self.columns
+            .get(&TypeId::of::<T>())
+            .and_then(|column| column.as_any().downcast_ref())
+    }
+
+    fn column_mut<T: 'static>(&mut self) -> Option<&mut Column<T>> {
+        self.columns
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|column| column.as_any_mut().downcast_mut())
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct EntityLocation {
+    pub archetype: usize,
+    pub row: usize,
+}
+
+/// Table storage to benchmark against `ComponentStorage`, see `benches/storage.rs`. Entities
+/// with the same components share an archetype, so iterating several components walks
+/// parallel `Vec`s instead of doing a map lookup per component per entity. Adding or removing
+/// a component moves the entity to another archetype, which makes structural changes more
+/// expensive. Only what the benchmarks need is here, nothing else uses it yet.
+pub struct ArchetypeStorage {
+    entities: SlotMap<Entity, EntityLocation>,
+    archetypes: Vec<Archetype>,
+    index: HashMap<Vec<TypeId>, usize>,
+}
+
+impl Default for ArchetypeStorage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ArchetypeStorage {
+    pub fn new() -> Self {
+        let mut index = HashMap::new();
+        index.insert(Vec::new(), 0);
+        Self {
+            entities: SlotMap::new(),
+            archetypes: vec![Archetype::new(Vec::new(), HashMap::new())],
+            index,
+        }
+    }
+
+    pub fn location(&self, entity: Entity) -> Option<EntityLocation> {
+        self.entities.get(entity).copied()
+    }
+
+    /// Creates an entity with no components
+    pub fn create(&mut self) -> Entity {
+        let row = self.archetypes[0].entities.len();
+        let entity = self.entities.insert(EntityLocation { archetype: 0, row });
+        self.archetypes[0].entities.push(entity);
+        entity
+    }
+
+    pub fn get<T>(&self, entity: Entity) -> Option<&T>
+    where
+        T: Component + 'static,
+    {
+        let location = self.location(entity)?;
+        let column = self.archetypes[location.archetype].column::<T>()?;
+        // SAFETY: shared access to the storage only ever creates shared refs
+        unsafe { (&*column.data.get()).get(location.row) }
+    }
+
+    /// Adds the component, moving the entity to the archetype that has it. Replacing an
+    /// existing component hands the old value back instead
+    pub fn insert<T>(&mut self, entity: Entity, component: T) -> Option<T>
+    where
+        T: Component + 'static,
+    {
+        let location = self.location(entity)?;
+        let source = &mut self.archetypes[location.archetype];
+        if let Some(column) = source.column_mut::<T>() {
+            let old = &mut column.data.get_mut()[location.row];
+            return Some(std::mem::replace(old, component));
+        }
+
+        let mut types = source.types.clone();
+        types.push(TypeId::of::<T>());
+        types.sort();
+        let target = self.archetype_with(types, location.archetype, |columns| {
+            columns.insert(TypeId::of::<T>(), Box::new(Column::<T>::new()));
+        });
+
+        self.move_entity(entity, target);
+        let column = self.archetypes[target].column_mut::<T>().unwrap();
+        column.data.get_mut().push(component);
+        None
+    }
+
+    /// Removes the component, moving the entity to the archetype without it
+    pub fn remove<T>(&mut self, entity: Entity) -> Option<T>
+    where
+        T: Component + 'static,
+    {
+        let location = self.location(entity)?;
+        if !self.archetypes[location.archetype].contains::<T>() {
+            return None;
+        }
+
+        let id = TypeId::of::<T>();
+        let mut types = self.archetypes[location.archetype].types.clone();
+        types.retain(|type_id| *type_id != id);
+        let target = self.archetype_with(types, location.archetype, |columns| {
+            columns.remove(&id);
+        });
+
+        // Take the column out so moving the rest of the row leaves it alone
+        let mut column = self.archetypes[location.archetype]
+            .columns
+            .remove(&id)
+            .unwrap();
+        self.move_entity(entity, target);
+        let value = column
+            .as_any_mut()
+            .downcast_mut::<Column<T>>()
+            .unwrap()
+            .data
+            .get_mut()
+            .swap_remove(location.row);
+        self.archetypes[location.archetype]
+            .columns
+            .insert(id, column);
+        Some(value)
+    }
+
+    /// Frees the entity and drops every component it had, false if it was already gone
+    pub fn despawn(&mut self, entity: Entity) -> bool {
+        let Some(location) = self.entities.remove(entity) else {
+            return false;
+        };
+        let archetype = &mut self.archetypes[location.archetype];
+        for column in archetype.columns.values_mut() {
+            column.swap_remove(location.row);
+        }
+        archetype.entities.swap_remove(location.row);
+        if let Some(moved) = archetype.entities.get(location.row) {
+            self.entities[*moved].row = location.row;
+        }
+        true
+    }
+
+    /// Iterates every entity that has all the components `Q` asks for, one archetype at a
+    /// time
+    pub fn query<'a, Q>(&'a mut self) -> impl Iterator<Item = (Entity, Q::Item<'a>)> + 'a
+    where
+        Q: TableQuery + 'a,
+    {
+        Q::update_access(&mut Access::new());
+        self.archetypes
+            .iter()
+            .filter(|archetype| !archetype.entities.is_empty() && Q::matches(archetype))
+            .flat_map(|archetype| {
+                // SAFETY: the storage is borrowed exclusively, the archetype matched and the
+                // access was just validated
+                let fetch = unsafe { Q::init_fetch(archetype) };
+                archetype
+                    .entities
+                    .iter()
+                    .enumerate()
+                    .map(move |(row, entity)| (*entity, unsafe { Q::fetch(&fetch, row) }))
+            })
+    }
+
+    // Finds the archetype for `types`, creating it from `source`'s columns the first time
+    fn archetype_with(
+        &mut self,
+        types: Vec<TypeId>,
+        source: usize,
+        edit: impl FnOnce(&mut HashMap<TypeId, Box<dyn TableColumn>>),
+    ) -> usize {
+        if let Some(index) = self.index.get(&types) {
+            return *index;
+        }
+
+        let mut columns: HashMap<TypeId, Box<dyn TableColumn>> = self.archetypes[source]
+            .columns
+            .iter()
+            .map(|(type_id, column)| (*type_id, column.empty()))
+            .collect();
+        edit(&mut columns);
+
+        let index = self.archetypes.len();
+        self.archetypes.push(Archetype::new(types.clone(), columns));
+        self.index.insert(types, index);
+        index
+    }
+
+    // Moves every component the target archetype also has, anything else is dropped
+    fn move_entity(&mut self, entity: Entity, target: usize) {
+        let location = self.entities[entity];
+        let (source, destination) = if location.archetype < target {
+            let (left, right) = self.archetypes.split_at_mut(target);
+            (&mut left[location.archetype], &mut right[0])
+        } else {
+            let (left, right) = self.archetypes.split_at_mut(location.archetype);
+            (&mut right[0], &mut left[target])
+        };
+
+        for (type_id, column) in source.columns.iter_mut() {
+            match destination.columns.get_mut(type_id) {
+                Some(other) => column.move_row(location.row, other.as_mut()),
+                None => column.swap_remove(location.row),
+            }
+        }
+
+        source.entities.swap_remove(location.row);
+        if let Some(moved) = source.entities.get(location.row) {
+            self.entities[*moved].row = location.row;
+        }
+        destination.entities.push(entity);
+        self.entities[entity] = EntityLocation {
+            archetype: target,
+            row: destination.entities.len() - 1,
+        };
+    }
+}
+
+/// What `ArchetypeStorage::query` can fetch, `&T`, `&mut T` and tuples of those.
+///
+/// # Safety
+/// `update_access` must report every component type `fetch` hands out, with `&mut` access
+/// reported as a write.
+pub unsafe trait TableQuery {
+    type Item<'a>;
+    type Fetch<'a>;
+
+    fn update_access(access: &mut Access);
+
+    fn matches(archetype: &Archetype) -> bool;
+
+    /// # Safety
+    /// `matches` must have returned true for the archetype and nothing else may access the
+    /// columns `Self` writes while the fetch is alive.
+    unsafe fn init_fetch(archetype: &Archetype) -> Self::Fetch<'_>;
+
+    /// # Safety
+    /// `row` must be in bounds and the caller must not hand out two items for the same row
+    /// at once when `Self` writes.
+    unsafe fn fetch<'a>(fetch: &Self::Fetch<'a>, row: usize) -> Self::Item<'a>;
+}
+
+unsafe impl<T> TableQuery for &T
+where
+    T: Component + 'static,
+{
+    type Item<'a> = &'a T;
+    type Fetch<'a> = &'a [T];
+
+    fn update_access(access: &mut Access) {
+        <&T as QueryData>::update_access(access);
+    }
+
+    fn matches(archetype: &Archetype) -> bool {
+        archetype.contains::<T>()
+    }
+
+    unsafe fn init_fetch(archetype: &Archetype) -> Self::Fetch<'_> {
+        &*archetype.column::<T>().unwrap().data.get()
+    }
+
+    unsafe fn fetch<'a>(fetch: &Self::Fetch<'a>, row: usize) -> Self::Item<'a> {
+        fetch.get_unchecked(row)
+    }
+}
+
+unsafe impl<T> TableQuery for &mut T
+where
+    T: Component + 'static,
+{
+    type Item<'a> = &'a mut T;
+    type Fetch<'a> = *mut T;
+
+    fn update_access(access: &mut Access) {
+        <&mut T as QueryData>::update_access(access);
+    }
+
+    fn matches(archetype: &Archetype) -> bool {
+        archetype.contains::<T>()
+    }
+
+    unsafe fn init_fetch(archetype: &Archetype) -> Self::Fetch<'_> {
+        (&mut *archetype.column::<T>().unwrap().data.get()).as_mut_ptr()
+    }
+
+    unsafe fn fetch<'a>(fetch: &Self::Fetch<'a>, row: usize) -> Self::Item<'a> {
+        &mut *fetch.add(row)
+    }
+}
+
+macro_rules! impl_table_query_tuple {
+    ($($name:ident),*) => {
+        #[allow(non_snake_case)]
+        unsafe impl<$($name: TableQuery),*> TableQuery for ($($name,)*) {
+            type Item<'a> = ($($name::Item<'a>,)*);
+            type Fetch<'a> = ($($name::Fetch<'a>,)*);
+
+            fn update_access(access: &mut Access) {
+                $($name::update_access(access);)*
+            }
+
+            fn matches(archetype: &Archetype) -> bool {
+                true $(&& $name::matches(archetype))*
+            }
+
+            unsafe fn init_fetch(archetype: &Archetype) -> Self::Fetch<'_> {
+                ($($name::init_fetch(archetype),)*)
+            }
+
+            unsafe fn fetch<'a>(fetch: &Self::Fetch<'a>, row: usize) -> Self::Item<'a> {
+                let ($($name,)*) = fetch;
+                ($($name::fetch($name, row),)*)
+            }
+        }
+    };
+}
+
+impl_table_query_tuple!(A);
+impl_table_query_tuple!(A, B);
+impl_table_query_tuple!(A, B, C);
+impl_table_query_tuple!(A, B, C, D);
@@ -1,12 +1,45 @@
-use std::{any::TypeId, collections::HashMap};
+use super::{Component, ComponentStorage, EcsError, Entity};
 
-use super::{Component, ComponentStorage, Entity};
-
-pub type Bundles = HashMap<TypeId, ()>;
+pub use bundle_derive::Bundle;
 
+/// A group of components spawned together. Derive it for structs, see `bundle_derive`
 pub trait Bundle {
+    /// Creates any column the bundle needs that doesn't exist yet
     fn register(store: &mut ComponentStorage);
-    fn add_data(self, store: &mut ComponentStorage, bundles: &Bundles) -> Option<Entity>;
+
+    /// Writes the bundle's own components into an entity that already exists
+    fn insert_components(
+        self,
+        store: &mut ComponentStorage,
+        entity: Entity,
+    ) -> Result<(), EcsError>;
+
+    /// Fills in anything the bundle's components require that the entity still lacks
+    fn insert_required(store: &mut ComponentStorage, entity: Entity) -> Result<(), EcsError>;
+
+    /// Drops whichever of the bundle's components the entity has
+    fn remove_from(store: &mut ComponentStorage, entity: Entity);
+
+    /// Writes every component into an entity that already exists. Required components go
+    /// in last, so a default never stands in for one the bundle brought itself
+    fn insert_into(self, store: &mut ComponentStorage, entity: Entity) -> Result<(), EcsError>
+    where
+        Self: Sized,
+    {
+        self.insert_components(store, entity)?;
+        Self::insert_required(store, entity)
+    }
+
+    /// Spawns a new entity with the bundle, registering its columns on the way
+    fn add_data(self, store: &mut ComponentStorage) -> Result<Entity, EcsError>
+    where
+        Self: Sized,
+    {
+        Self::register(store);
+        let entity = store.create();
+        self.insert_into(store, entity)?;
+        Ok(entity)
+    }
 }
 
 impl<C> Bundle for C
@@ -14,17 +47,24 @@ where
     C: Component + 'static,
 {
     fn register(store: &mut ComponentStorage) {
-        store.register::<Self>();
+        store.try_register::<Self>();
+    }
+
+    fn insert_components(
+        self,
+        store: &mut ComponentStorage,
+        entity: Entity,
+    ) -> Result<(), EcsError> {
+        store.insert_without_required::<C>(entity, self)?;
+        Ok(())
+    }
+
+    fn insert_required(store: &mut ComponentStorage, entity: Entity) -> Result<(), EcsError> {
+        store.insert_required::<C>(entity)
     }
 
-    fn add_data(self, store: &mut ComponentStorage, bundles: &Bundles) -> Option<Entity> {
-        if let Some(_) = bundles.get(&TypeId::of::<Self>()) {
-            let entity = store.create();
-            store.get_mut::<C>().unwrap().insert(entity, self);
-            Some(entity)
-        } else {
-            None
-        }
+    fn remove_from(store: &mut ComponentStorage, entity: Entity) {
+        store.discard::<C>(entity);
     }
 }
 
@@ -36,14 +76,21 @@ where
         store.try_register::<C>();
     }
 
-    fn add_data(self, store: &mut ComponentStorage, bundles: &Bundles) -> Option<Entity> {
-        if let Some(_) = bundles.get(&TypeId::of::<Self>()) {
-            let entity = store.create();
-            store.get_mut::<C>().unwrap().insert(entity, self.0);
-            Some(entity)
-        } else {
-            None
-        }
+    fn insert_components(
+        self,
+        store: &mut ComponentStorage,
+        entity: Entity,
+    ) -> Result<(), EcsError> {
+        store.insert_without_required::<C>(entity, self.0)?;
+        Ok(())
+    }
+
+    fn insert_required(store: &mut ComponentStorage, entity: Entity) -> Result<(), EcsError> {
+        store.insert_required::<C>(entity)
+    }
+
+    fn remove_from(store: &mut ComponentStorage, entity: Entity) {
+        store.discard::<C>(entity);
     }
 }
 
@@ -57,15 +104,24 @@ where
         store.try_register::<T2>();
     }
 
-    fn add_data(self, store: &mut ComponentStorage, bundles: &Bundles) -> Option<Entity> {
-        if let Some(_) = bundles.get(&TypeId::of::<Self>()) {
-            let entity = store.create();
-            store.get_mut::<T1>().unwrap().insert(entity, self.0);
-            store.get_mut::<T2>().unwrap().insert(entity, self.1);
-            Some(entity)
-        } else {
-            None
-        }
+    fn insert_components(
+        self,
+        store: &mut ComponentStorage,
+        entity: Entity,
+    ) -> Result<(), EcsError> {
+        store.insert_without_required::<T1>(entity, self.0)?;
+        store.insert_without_required::<T2>(entity, self.1)?;
+        Ok(())
+    }
+
+    fn insert_required(store: &mut ComponentStorage, entity: Entity) -> Result<(), EcsError> {
+        store.insert_required::<T1>(entity)?;
+        store.insert_required::<T2>(entity)
+    }
+
+    fn remove_from(store: &mut ComponentStorage, entity: Entity) {
+        store.discard::<T1>(entity);
+        store.discard::<T2>(entity);
     }
 }
 
@@ -81,15 +137,26 @@ where
         store.try_register::<T3>();
     }
 
-    fn add_data(self, store: &mut ComponentStorage, bundles: &Bundles) -> Option<Entity> {
-        if let Some(_) = bundles.get(&TypeId::of::<Self>()) {
-            let entity = store.create();
-            store.get_mut::<T1>().unwrap().insert(entity, self.0);
-            store.get_mut::<T2>().unwrap().insert(entity, self.1);
-            store.get_mut::<T3>().unwrap().insert(entity, self.2);
-            Some(entity)
-        } else {
-            None
-        }
+    fn insert_components(
+        self,
+        store: &mut ComponentStorage,
+        entity: Entity,
+    ) -> Result<(), EcsError> {
+        store.insert_without_required::<T1>(entity, self.0)?;
+        store.insert_without_required::<T2>(entity, self.1)?;
+        store.insert_without_required::<T3>(entity, self.2)?;
+        Ok(())
+    }
+
+    fn insert_required(store: &mut ComponentStorage, entity: Entity) -> Result<(), EcsError> {
+        store.insert_required::<T1>(entity)?;
+        store.insert_required::<T2>(entity)?;
+        store.insert_required::<T3>(entity)
+    }
+
+    fn remove_from(store: &mut ComponentStorage, entity: Entity) {
+        store.discard::<T1>(entity);
+        store.discard::<T2>(entity);
+        store.discard::<T3>(entity);
     }
 }
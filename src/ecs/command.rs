@@ -0,0 +1,138 @@
+use std::sync::Mutex;
+
+use super::{Bundle, Component, EcsError, Entity, SystemAccess, SystemParam, Tick, ECS};
+
+pub type Command = Box<dyn FnOnce(&mut ECS) -> Result<(), EcsError> + Send>;
+
+/// Structural changes recorded by systems, applied by `ECS::apply_commands` once nothing is
+/// iterating any more
+#[derive(Default)]
+pub struct CommandQueue {
+    queue: Mutex<Vec<Command>>,
+}
+
+impl CommandQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn append(&self, commands: &mut Vec<Command>) {
+        self.queue.lock().unwrap().append(commands);
+    }
+
+    pub fn take(&mut self) -> Vec<Command> {
+        std::mem::take(self.queue.get_mut().unwrap())
+    }
+}
+
+/// Records spawns, despawns, inserts and removals from inside a system, they are applied at
+/// the end of the stage
+pub struct Commands<'w> {
+    queue: &'w CommandQueue,
+    buffer: Vec<Command>,
+}
+
+impl<'w> Commands<'w> {
+    pub fn new(queue: &'w CommandQueue) -> Self {
+        Self {
+            queue,
+            buffer: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, command: impl FnOnce(&mut ECS) -> Result<(), EcsError> + Send + 'static) {
+        self.buffer.push(Box::new(command));
+    }
+
+    pub fn spawn<B>(&mut self, bundle: B)
+    where
+        B: Bundle + Send + 'static,
+    {
+        self.add(move |ecs| ecs.add_bundle(bundle).map(|_| ()));
+    }
+
+    pub fn entity(&mut self, entity: Entity) -> EntityCommands<'_, 'w> {
+        EntityCommands {
+            entity,
+            commands: self,
+        }
+    }
+
+    pub fn insert<T>(&mut self, entity: Entity, component: T)
+    where
+        T: Component + 'static,
+    {
+        self.add(move |ecs| {
+            ecs.store.try_register::<T>();
+            ecs.add_component(entity, component)
+        });
+    }
+
+    pub fn remove<T>(&mut self, entity: Entity)
+    where
+        T: Component + 'static,
+    {
+        self.add(move |ecs| ecs.remove_component::<T>(entity).map(|_| ()));
+    }
+
+    pub fn despawn(&mut self, entity: Entity) {
+        self.add(move |ecs| ecs.despawn(entity));
+    }
+}
+
+impl<'w> Drop for Commands<'w> {
+    fn drop(&mut self) {
+        self.queue.append(&mut self.buffer);
+    }
+}
+
+/// Commands aimed at one existing entity
+pub struct EntityCommands<'a, 'w> {
+    entity: Entity,
+    commands: &'a mut Commands<'w>,
+}
+
+impl<'a, 'w> EntityCommands<'a, 'w> {
+    pub fn id(&self) -> Entity {
+        self.entity
+    }
+
+    pub fn insert<T>(self, component: T) -> Self
+    where
+        T: Component + 'static,
+    {
+        self.commands.insert(self.entity, component);
+        self
+    }
+
+    pub fn remove<T>(self) -> Self
+    where
+        T: Component + 'static,
+    {
+        self.commands.remove::<T>(self.entity);
+        self
+    }
+
+    pub fn despawn(self) {
+        self.commands.despawn(self.entity);
+    }
+}
+
+// The queue is behind a mutex so any number of systems can hold `Commands` at once
+unsafe impl<'a> SystemParam for Commands<'a> {
+    type State = ();
+    type Item<'w> = Commands<'w>;
+
+    fn init_state(_ecs: &mut ECS) {}
+
+    fn update_access(_access: &mut SystemAccess) {}
+
+    unsafe fn get_param<'w>(
+        _state: &'w mut (),
+        ecs: &'w ECS<'w>,
+        _last_run: Tick,
+        _this_run: Tick,
+    ) -> Commands<'w> {
+        Commands::new(&ecs.commands)
+    }
+}
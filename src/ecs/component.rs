@@ -1,21 +1,130 @@
-use std::marker::PhantomData;
+use std::{
+    any::{type_name, TypeId},
+    cell::UnsafeCell,
+    collections::{HashMap, VecDeque},
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+};
 
-use anymap::AnyMap;
-use slotmap::{SecondaryMap, SlotMap};
+use anymap::{any::Any, Map};
+use slotmap::{SecondaryMap, SlotMap, SparseSecondaryMap};
 
-use super::Entity;
+use super::{
+    ComponentHook, ComponentHooks, ComponentTicks, EcsError, Entity, Lifecycle, Mut, Reflect, Tick,
+    Triggered, TypeRegistry,
+};
 
-pub type ComponentMap = AnyMap;
+pub type ComponentMap = Map<dyn Any + Send + Sync>;
 
-pub trait Component {}
+/// Components are shared between systems running on different threads, and reflected so
+/// tools can get at them by name
+pub trait Component: Reflect + Send + Sync {
+    /// Where the column for this component lives, see `StorageKind`
+    const STORAGE: StorageKind = StorageKind::Dense;
+
+    /// Lists the components an entity with this one must also have, see `RequiredComponents`
+    fn required_components(_required: &mut RequiredComponents) {}
+}
+
+pub use component_macro::Component;
+
+/// `Dense` columns index straight into a `Vec` by entity slot, which is fastest to look up
+/// and iterate but takes memory for every entity ever allocated. `Sparse` columns hash the
+/// entity instead, better for components only a handful of entities have or that get added
+/// and removed a lot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StorageKind {
+    Dense,
+    Sparse,
+}
 
 pub type EntityAllocator<K, V> = SlotMap<K, V>;
 
 pub type EntityMap<K, V> = SecondaryMap<K, V>;
 
+pub type SparseEntityMap<K, V> = SparseSecondaryMap<K, V>;
+
+// Drops one entity's value from a column without knowing the column's type, saying whether
+// there was one
+type ColumnRemover = fn(&mut ComponentMap, Entity) -> bool;
+
+// Inserts one required component if the entity is missing it, holding on to whatever
+// builds the value
+type RequiredInsert =
+    Arc<dyn Fn(&mut ComponentStorage, Entity) -> Result<(), EcsError> + Send + Sync>;
+
+struct RequiredComponent {
+    type_id: TypeId,
+    register: fn(&mut ComponentStorage),
+    insert: RequiredInsert,
+}
+
+/// Whenever a component is added, every component it requires that the entity doesn't
+/// have yet is inserted too, from `Default` or from the constructor it was given
+#[derive(Default)]
+pub struct RequiredComponents {
+    components: Vec<RequiredComponent>,
+}
+
+impl RequiredComponents {
+    pub fn add<T>(&mut self)
+    where
+        T: Component + Default + 'static,
+    {
+        self.add_with(T::default);
+    }
+
+    /// Requires `T`, built by `constructor` whenever it has to be filled in
+    pub fn add_with<T>(&mut self, constructor: fn() -> T)
+    where
+        T: Component + 'static,
+    {
+        if self.contains::<T>() {
+            return;
+        }
+        self.components.push(RequiredComponent {
+            type_id: TypeId::of::<T>(),
+            register: |store| store.try_register::<T>(),
+            insert: Arc::new(move |store, entity| {
+                if !store.get::<T>()?.contains_key(entity) {
+                    store.insert(entity, constructor())?;
+                }
+                Ok(())
+            }),
+        });
+    }
+
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.components
+            .iter()
+            .any(|component| component.type_id == TypeId::of::<T>())
+    }
+
+    pub fn len(&self) -> usize {
+        self.components.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+}
+
 pub struct ComponentStorage<'a> {
     pub allocator: EntityAllocator<Entity, ()>,
     pub components: ComponentMap,
+    removers: Vec<(TypeId, ColumnRemover)>,
+    // Only components that require something get an entry
+    required: HashMap<TypeId, Vec<RequiredInsert>>,
+    // Only components with a hook or an observer get an entry, nothing else is queued
+    hooks: HashMap<TypeId, ComponentHooks>,
+    triggered: VecDeque<Triggered>,
+    pub types: TypeRegistry,
+    // Atomic so systems running in parallel can each take a tick through a shared borrow
+    change_tick: AtomicU32,
+    pub last_change_tick: Tick,
     _marker: PhantomData<&'a ()>,
 }
 
@@ -24,6 +133,13 @@ impl<'a> ComponentStorage<'a> {
         Self {
             allocator: EntityAllocator::new(),
             components: ComponentMap::new(),
+            removers: Vec::new(),
+            required: HashMap::new(),
+            hooks: HashMap::new(),
+            triggered: VecDeque::new(),
+            types: TypeRegistry::new(),
+            change_tick: AtomicU32::new(1),
+            last_change_tick: Tick::new(0),
             _marker: PhantomData,
         }
     }
@@ -36,31 +152,401 @@ impl<'a> ComponentStorage<'a> {
     where
         T: Component + 'static,
     {
-        let new_component: EntityMap<Entity, T> = EntityMap::new();
+        // Registering again would swap the column for an empty one and lose its data
+        if self.components.contains::<ComponentColumn<T>>() {
+            return;
+        }
+        let new_component: ComponentColumn<T> = ComponentColumn::new();
         self.components.insert(new_component);
+        self.removers
+            .push((TypeId::of::<T>(), |components, entity| {
+                components
+                    .get_mut::<ComponentColumn<T>>()
+                    .and_then(|column| column.remove(entity))
+                    .is_some()
+            }));
+        self.types.register::<T>();
+
+        let mut required = RequiredComponents::default();
+        T::required_components(&mut required);
+        if !required.is_empty() {
+            for component in &required.components {
+                (component.register)(self);
+            }
+            let inserts = required.components.into_iter().map(|c| c.insert).collect();
+            self.required.insert(TypeId::of::<T>(), inserts);
+        }
     }
 
     pub fn try_register<T>(&mut self)
     where
         T: Component + 'static,
     {
-        if self.components.get::<EntityMap<Entity, T>>().is_none() {
+        if self.components.get::<ComponentColumn<T>>().is_none() {
             self.register::<T>()
         }
     }
 
-    pub fn get<T>(&self) -> Option<&EntityMap<Entity, T>>
+    pub fn get<T>(&self) -> Result<&ComponentColumn<T>, EcsError>
+    where
+        T: Component + 'static,
+    {
+        self.components
+            .get::<ComponentColumn<T>>()
+            .ok_or(EcsError::UnregisteredComponent(type_name::<T>()))
+    }
+
+    pub fn get_mut<T>(&mut self) -> Result<&mut ComponentColumn<T>, EcsError>
     where
         T: Component + 'static,
     {
-        self.components.get::<EntityMap<Entity, T>>()
+        self.components
+            .get_mut::<ComponentColumn<T>>()
+            .ok_or(EcsError::UnregisteredComponent(type_name::<T>()))
     }
 
-    pub fn get_mut<T>(&mut self) -> Option<&mut EntityMap<Entity, T>>
+    /// Inserts into an already registered column, stamping the current change tick, along
+    /// with anything it requires that's missing. Hands back the component it replaced, if any
+    pub fn insert<T>(&mut self, entity: Entity, component: T) -> Result<Option<T>, EcsError>
     where
         T: Component + 'static,
     {
-        self.components.get_mut::<EntityMap<Entity, T>>()
+        let replaced = self.insert_without_required(entity, component)?;
+        if replaced.is_none() {
+            self.insert_required::<T>(entity)?;
+        }
+        Ok(replaced)
+    }
+
+    /// `insert` minus the required components, for bundles that fill those in once all of
+    /// their own components are in place
+    pub fn insert_without_required<T>(
+        &mut self,
+        entity: Entity,
+        component: T,
+    ) -> Result<Option<T>, EcsError>
+    where
+        T: Component + 'static,
+    {
+        self.check_alive(entity)?;
+        let tick = self.change_tick();
+        let replaced = self.get_mut::<T>()?.insert(entity, component, tick);
+        if replaced.is_none() {
+            self.trigger(Lifecycle::Add, TypeId::of::<T>(), entity);
+        }
+        self.trigger(Lifecycle::Insert, TypeId::of::<T>(), entity);
+        Ok(replaced)
+    }
+
+    /// Inserts whatever `T` requires that the entity doesn't have yet
+    pub fn insert_required<T>(&mut self, entity: Entity) -> Result<(), EcsError>
+    where
+        T: Component + 'static,
+    {
+        if let Some(required) = self.required.get(&TypeId::of::<T>()) {
+            for insert in required.clone() {
+                insert(self, entity)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn remove<T>(&mut self, entity: Entity) -> Result<T, EcsError>
+    where
+        T: Component + 'static,
+    {
+        self.check_alive(entity)?;
+        let removed = self
+            .get_mut::<T>()?
+            .remove(entity)
+            .ok_or(EcsError::MissingComponent {
+                entity,
+                component: type_name::<T>(),
+            })?;
+        self.trigger(Lifecycle::Remove, TypeId::of::<T>(), entity);
+        Ok(removed)
+    }
+
+    /// Drops the entity's `T` if it has one, an unregistered column counts as not having it
+    pub fn discard<T>(&mut self, entity: Entity)
+    where
+        T: Component + 'static,
+    {
+        let removed = self
+            .get_mut::<T>()
+            .ok()
+            .and_then(|column| column.remove(entity));
+        if removed.is_some() {
+            self.trigger(Lifecycle::Remove, TypeId::of::<T>(), entity);
+        }
+    }
+
+    /// Frees the entity and drops every component it had
+    pub fn despawn(&mut self, entity: Entity) -> Result<(), EcsError> {
+        self.allocator
+            .remove(entity)
+            .ok_or(EcsError::DeadEntity(entity))?;
+        for i in 0..self.removers.len() {
+            let (type_id, remove) = self.removers[i];
+            if remove(&mut self.components, entity) {
+                self.trigger(Lifecycle::Remove, type_id, entity);
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs `hook` on the `ECS` whenever `T` is added to an entity that didn't have it
+    pub fn on_add<T: Component + 'static>(&mut self, hook: ComponentHook) {
+        self.watch(TypeId::of::<T>()).on_add.push(hook);
+    }
+
+    /// Runs `hook` on every insert of `T`, whether it was added or replaced one
+    pub fn on_insert<T: Component + 'static>(&mut self, hook: ComponentHook) {
+        self.watch(TypeId::of::<T>()).on_insert.push(hook);
+    }
+
+    /// Runs `hook` once `T` has been taken off an entity, despawning included
+    pub fn on_remove<T: Component + 'static>(&mut self, hook: ComponentHook) {
+        self.watch(TypeId::of::<T>()).on_remove.push(hook);
+    }
+
+    pub fn hooks(&self, component: TypeId) -> Option<&ComponentHooks> {
+        self.hooks.get(&component)
+    }
+
+    // Starts queueing the component's changes, for hooks or for an observer
+    pub(crate) fn watch(&mut self, component: TypeId) -> &mut ComponentHooks {
+        self.hooks.entry(component).or_default()
+    }
+
+    fn trigger(&mut self, lifecycle: Lifecycle, component: TypeId, entity: Entity) {
+        if self.hooks.contains_key(&component) {
+            self.triggered.push_back(Triggered {
+                lifecycle,
+                component,
+                entity,
+            });
+        }
+    }
+
+    pub(crate) fn next_triggered(&mut self) -> Option<Triggered> {
+        self.triggered.pop_front()
+    }
+
+    pub fn check_alive(&self, entity: Entity) -> Result<(), EcsError> {
+        if self.contains(entity) {
+            Ok(())
+        } else {
+            Err(EcsError::DeadEntity(entity))
+        }
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.allocator.contains_key(entity)
+    }
+
+    pub fn change_tick(&self) -> Tick {
+        Tick::new(self.change_tick.load(Ordering::Acquire))
+    }
+
+    /// Hands out the tick a system run stamps its changes with, anything changed afterwards
+    /// gets a later tick
+    pub fn increment_change_tick(&self) -> Tick {
+        Tick::new(self.change_tick.fetch_add(1, Ordering::AcqRel))
+    }
+
+    /// Starts a new change detection window, anything stamped before this call is no longer
+    /// reported by `Added` or `Changed`
+    pub fn clear_trackers(&mut self) {
+        self.last_change_tick = self.increment_change_tick();
+    }
+}
+
+impl<'a> Default for ComponentStorage<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Every value sits in its own UnsafeCell so a query can hand out `&mut` to several
+// entities of the same column while the map itself is only borrowed shared
+struct ComponentCell<T> {
+    value: UnsafeCell<T>,
+    ticks: UnsafeCell<ComponentTicks>,
+}
+
+enum ColumnData<T> {
+    Dense(EntityMap<Entity, ComponentCell<T>>),
+    Sparse(SparseEntityMap<Entity, ComponentCell<T>>),
+}
+
+// Lets the dense and sparse iterators come out of the same function
+enum EitherIter<D, S> {
+    Dense(D),
+    Sparse(S),
+}
+
+impl<D, S> Iterator for EitherIter<D, S>
+where
+    D: Iterator,
+    S: Iterator<Item = D::Item>,
+{
+    type Item = D::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            EitherIter::Dense(iter) => iter.next(),
+            EitherIter::Sparse(iter) => iter.next(),
+        }
+    }
+}
+
+pub struct ComponentColumn<T> {
+    data: ColumnData<T>,
+}
+
+// SAFETY: the cells are only written through `&mut self` or `get_unchecked_mut`, and the
+// scheduler never runs two systems that touch the same column mutably at once
+unsafe impl<T: Send> Send for ComponentColumn<T> {}
+unsafe impl<T: Send + Sync> Sync for ComponentColumn<T> {}
+
+impl<T> ComponentColumn<T>
+where
+    T: Component,
+{
+    pub fn new() -> Self {
+        let data = match T::STORAGE {
+            StorageKind::Dense => ColumnData::Dense(EntityMap::new()),
+            StorageKind::Sparse => ColumnData::Sparse(SparseEntityMap::new()),
+        };
+        Self { data }
+    }
+}
+
+impl<T> ComponentColumn<T> {
+    pub fn storage_kind(&self) -> StorageKind {
+        match self.data {
+            ColumnData::Dense(_) => StorageKind::Dense,
+            ColumnData::Sparse(_) => StorageKind::Sparse,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.data {
+            ColumnData::Dense(data) => data.len(),
+            ColumnData::Sparse(data) => data.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn contains_key(&self, entity: Entity) -> bool {
+        self.cell(entity).is_some()
+    }
+
+    fn cell(&self, entity: Entity) -> Option<&ComponentCell<T>> {
+        match &self.data {
+            ColumnData::Dense(data) => data.get(entity),
+            ColumnData::Sparse(data) => data.get(entity),
+        }
+    }
+
+    fn cell_mut(&mut self, entity: Entity) -> Option<&mut ComponentCell<T>> {
+        match &mut self.data {
+            ColumnData::Dense(data) => data.get_mut(entity),
+            ColumnData::Sparse(data) => data.get_mut(entity),
+        }
+    }
+
+    fn cells(&self) -> impl Iterator<Item = (Entity, &ComponentCell<T>)> + '_ {
+        match &self.data {
+            ColumnData::Dense(data) => EitherIter::Dense(data.iter()),
+            ColumnData::Sparse(data) => EitherIter::Sparse(data.iter()),
+        }
+    }
+
+    pub fn get(&self, entity: Entity) -> Option<&T> {
+        // SAFETY: shared access to the column only ever creates shared refs unless the caller
+        // went through `get_unchecked_mut`, whose contract forbids overlapping with this
+        self.cell(entity).map(|cell| unsafe { &*cell.value.get() })
+    }
+
+    /// Writing through the returned `Mut` marks the component changed at `tick`
+    pub fn get_mut(&mut self, entity: Entity, tick: Tick) -> Option<Mut<'_, T>> {
+        self.cell_mut(entity)
+            .map(|cell| Mut::new(cell.value.get_mut(), cell.ticks.get_mut(), tick))
+    }
+
+    pub fn get_ticks(&self, entity: Entity) -> Option<ComponentTicks> {
+        self.cell(entity).map(|cell| unsafe { *cell.ticks.get() })
+    }
+
+    /// # Safety
+    /// The caller must make sure no other reference to this entity's component is alive for
+    /// the returned lifetime, e.g. by validating query access up front.
+    #[allow(clippy::mut_from_ref)]
+    pub unsafe fn get_unchecked_mut(
+        &self,
+        entity: Entity,
+    ) -> Option<(&mut T, &mut ComponentTicks)> {
+        self.cell(entity)
+            .map(|cell| (&mut *cell.value.get(), &mut *cell.ticks.get()))
+    }
+
+    /// Replacing an existing component counts as a change, not an addition
+    pub fn insert(&mut self, entity: Entity, component: T, tick: Tick) -> Option<T> {
+        if let Some(cell) = self.cell_mut(entity) {
+            cell.ticks.get_mut().changed = tick;
+            return Some(std::mem::replace(cell.value.get_mut(), component));
+        }
+        let cell = ComponentCell {
+            value: UnsafeCell::new(component),
+            ticks: UnsafeCell::new(ComponentTicks::new(tick)),
+        };
+        match &mut self.data {
+            ColumnData::Dense(data) => data.insert(entity, cell),
+            ColumnData::Sparse(data) => data.insert(entity, cell),
+        };
+        None
+    }
+
+    pub fn remove(&mut self, entity: Entity) -> Option<T> {
+        let cell = match &mut self.data {
+            ColumnData::Dense(data) => data.remove(entity),
+            ColumnData::Sparse(data) => data.remove(entity),
+        };
+        cell.map(|cell| cell.value.into_inner())
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.cells().map(|(entity, _)| entity)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &T)> + '_ {
+        self.cells()
+            .map(|(entity, cell)| (entity, unsafe { &*cell.value.get() }))
+    }
+
+    pub fn iter_mut(&mut self, tick: Tick) -> impl Iterator<Item = (Entity, Mut<'_, T>)> + '_ {
+        let cells = match &mut self.data {
+            ColumnData::Dense(data) => EitherIter::Dense(data.iter_mut()),
+            ColumnData::Sparse(data) => EitherIter::Sparse(data.iter_mut()),
+        };
+        cells.map(move |(entity, cell)| {
+            let ComponentCell { value, ticks } = cell;
+            (entity, Mut::new(value.get_mut(), ticks.get_mut(), tick))
+        })
+    }
+}
+
+impl<T> Default for ComponentColumn<T>
+where
+    T: Component,
+{
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -68,11 +554,11 @@ pub trait ComponentStore {
     type Item: Component;
 
     fn get(&self, entity: Entity) -> Option<&Self::Item>;
-    fn get_mut(&mut self, entity: Entity) -> Option<&mut Self::Item>;
-    fn insert(&mut self, entity: Entity, component: Self::Item);
+    fn get_mut(&mut self, entity: Entity, tick: Tick) -> Option<Mut<'_, Self::Item>>;
+    fn insert(&mut self, entity: Entity, component: Self::Item, tick: Tick);
 }
 
-impl<T> ComponentStore for EntityMap<Entity, T>
+impl<T> ComponentStore for ComponentColumn<T>
 where
     T: Component,
 {
@@ -82,11 +568,11 @@ where
         self.get(entity)
     }
 
-    fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
-        self.get_mut(entity)
+    fn get_mut(&mut self, entity: Entity, tick: Tick) -> Option<Mut<'_, T>> {
+        self.get_mut(entity, tick)
     }
 
-    fn insert(&mut self, entity: Entity, component: T) {
-        self.insert(entity, component);
+    fn insert(&mut self, entity: Entity, component: T, tick: Tick) {
+        self.insert(entity, component, tick);
     }
 }
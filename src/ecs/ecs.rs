@@ -1,7 +1,12 @@
-use std::any::TypeId;
+use std::{any::type_name, mem};
 
-use super::{Bundle, Bundles, Component, ComponentStorage, EntityMap, ResourceStorage};
+use super::{
+    Bundle, Children, CommandQueue, Component, ComponentColumn, ComponentStorage, EcsError,
+    FromWorld, Mut, NonSendStorage, Observers, Parent, Reflect, ResourceStorage, SceneRegistry,
+    TypeRegistry,
+};
 
+use serde::{de::DeserializeOwned, Serialize};
 use slotmap::DefaultKey;
 
 pub type Entity = DefaultKey;
@@ -9,15 +14,36 @@ pub type Entity = DefaultKey;
 pub struct ECS<'a> {
     pub store: ComponentStorage<'a>,
     pub resources: ResourceStorage,
-    pub bundles: Bundles,
+    pub non_send: NonSendStorage,
+    pub commands: CommandQueue,
+    pub scene_registry: SceneRegistry,
+    pub observers: Observers,
+    // Failed commands wait here until someone takes them, see `take_command_errors`
+    command_errors: Vec<EcsError>,
+    // Set while hooks run so anything they change is left for the loop already running
+    flushing_hooks: bool,
+}
+
+impl<'a> Default for ECS<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<'a> ECS<'a> {
     pub fn new() -> Self {
+        let mut store = ComponentStorage::new();
+        store.register::<Parent>();
+        store.register::<Children>();
         Self {
-            store: ComponentStorage::new(),
+            store,
             resources: ResourceStorage::new(),
-            bundles: Bundles::new(),
+            non_send: NonSendStorage::new(),
+            commands: CommandQueue::new(),
+            scene_registry: SceneRegistry::new(),
+            observers: Observers::new(),
+            command_errors: Vec::new(),
+            flushing_hooks: false,
         }
     }
 
@@ -32,47 +58,186 @@ impl<'a> ECS<'a> {
         self.store.register::<T>();
     }
 
-    pub fn get_component<T>(&self) -> Option<&EntityMap<Entity, T>>
+    /// Registers the component and lets scenes save and load it
+    pub fn register_scene_component<T>(&mut self)
+    where
+        T: Component + Serialize + DeserializeOwned + 'static,
+    {
+        self.store.try_register::<T>();
+        self.scene_registry.register::<T>();
+    }
+
+    pub fn type_registry(&self) -> &TypeRegistry {
+        &self.store.types
+    }
+
+    /// Finds a component on the entity by its type name, for tools that only have a string
+    pub fn reflect_component(&self, entity: Entity, name: &str) -> Option<&dyn Reflect> {
+        self.store.reflect(entity, name)
+    }
+
+    pub fn reflect_component_mut(
+        &mut self,
+        entity: Entity,
+        name: &str,
+    ) -> Option<&mut dyn Reflect> {
+        self.store.reflect_mut(entity, name)
+    }
+
+    pub fn get_component<T>(&self) -> Result<&ComponentColumn<T>, EcsError>
     where
         T: Component + 'static,
     {
         self.store.get()
     }
 
-    pub fn get_mut_component<T>(&mut self) -> Option<&mut EntityMap<Entity, T>>
+    pub fn get_mut_component<T>(&mut self) -> Result<&mut ComponentColumn<T>, EcsError>
     where
         T: Component + 'static,
     {
         self.store.get_mut::<T>()
     }
 
-    pub fn add_component<T>(&mut self, entity: Entity, component: T)
+    pub fn add_component<T>(&mut self, entity: Entity, component: T) -> Result<(), EcsError>
+    where
+        T: Component + 'static,
+    {
+        let result = self.store.insert(entity, component);
+        self.flush_hooks();
+        result.map(|_| ())
+    }
+
+    pub fn remove_component<T>(&mut self, entity: Entity) -> Result<T, EcsError>
     where
         T: Component + 'static,
     {
-        self.get_mut_component::<T>()
-            .unwrap()
-            .insert(entity, component);
+        let result = self.store.remove::<T>(entity);
+        self.flush_hooks();
+        result
+    }
+
+    /// Removes the entity and all of its components. Anything attached to it is left in
+    /// place as a root, see `despawn_recursive` to take the whole subtree
+    pub fn despawn(&mut self, entity: Entity) -> Result<(), EcsError> {
+        self.store.check_alive(entity)?;
+        self.detach(entity)?;
+        self.store.despawn(entity)?;
+        self.flush_hooks();
+        Ok(())
     }
 
-    pub fn query<T>(&self, entity: Entity) -> Option<&T>
+    /// Runs the component hooks and observers for every change queued so far. `ECS` methods
+    /// do this themselves, it's only needed after going straight through `store`
+    pub fn flush_hooks(&mut self) {
+        if self.flushing_hooks {
+            return;
+        }
+        self.flushing_hooks = true;
+        while let Some(triggered) = self.store.next_triggered() {
+            let hooks = match self.store.hooks(triggered.component) {
+                Some(hooks) => hooks.get(triggered.lifecycle).to_vec(),
+                None => Vec::new(),
+            };
+            for hook in hooks {
+                hook(self, triggered.entity);
+            }
+            // Taken out so they can have the world, any added meanwhile go on the end
+            let mut observers = mem::take(&mut self.observers);
+            observers.run(self, triggered);
+            observers.append(&mut self.observers);
+            self.observers = observers;
+        }
+        self.observers.retain_alive(&self.store);
+        self.flushing_hooks = false;
+    }
+
+    /// Applies everything systems queued through `Commands` since the last sync point. A
+    /// failing command is skipped and the rest still run, the system that queued it has long
+    /// finished so its error is kept for `take_command_errors`
+    pub fn apply_commands(&mut self) {
+        self.flush_hooks();
+        for command in self.commands.take() {
+            if let Err(error) = command(self) {
+                self.command_errors.push(error);
+            }
+        }
+    }
+
+    /// Hands over the errors of every command that failed since the last call
+    pub fn take_command_errors(&mut self) -> Vec<EcsError> {
+        mem::take(&mut self.command_errors)
+    }
+
+    pub fn query<T>(&self, entity: Entity) -> Result<&T, EcsError>
     where
         T: Component + 'static,
     {
-        self.get_component::<T>().unwrap().get(entity)
+        self.store.check_alive(entity)?;
+        self.get_component::<T>()?
+            .get(entity)
+            .ok_or(EcsError::MissingComponent {
+                entity,
+                component: type_name::<T>(),
+            })
     }
 
-    pub fn query_mut<T>(&mut self, entity: Entity) -> Option<&mut T>
+    /// Writing through the returned `Mut` marks the component changed
+    pub fn query_mut<T>(&mut self, entity: Entity) -> Result<Mut<'_, T>, EcsError>
     where
         T: Component + 'static,
     {
-        self.get_mut_component::<T>().unwrap().get_mut(entity)
+        self.store.check_alive(entity)?;
+        let tick = self.store.change_tick();
+        self.get_mut_component::<T>()?
+            .get_mut(entity, tick)
+            .ok_or(EcsError::MissingComponent {
+                entity,
+                component: type_name::<T>(),
+            })
+    }
+
+    pub fn clear_trackers(&mut self) {
+        self.store.clear_trackers();
     }
 
-    pub fn add_resource<T: 'static>(&mut self, resource: T) {
+    pub fn add_resource<T: Send + Sync + 'static>(&mut self, resource: T) {
         self.resources.insert(resource)
     }
 
+    /// Adds the resource unless it's already there
+    pub fn init_resource<T: FromWorld + Send + Sync + 'static>(&mut self) {
+        if !self.resources.contains::<T>() {
+            let resource = T::from_world(self);
+            self.resources.insert(resource);
+        }
+    }
+
+    pub fn remove_resource<T: 'static>(&mut self) -> Option<T> {
+        self.resources.remove::<T>()
+    }
+
+    pub fn contains_resource<T: 'static>(&self) -> bool {
+        self.resources.contains::<T>()
+    }
+
+    /// Takes the resource out for the length of `scope` so it can be borrowed mutably next
+    /// to the rest of the world, then puts it back. Inside `scope` the world doesn't have it
+    pub fn resource_scope<T, R>(
+        &mut self,
+        scope: impl FnOnce(&mut ECS<'a>, &mut T) -> R,
+    ) -> Result<R, EcsError>
+    where
+        T: Send + Sync + 'static,
+    {
+        let mut resource = self
+            .resources
+            .remove::<T>()
+            .ok_or(EcsError::MissingResource(type_name::<T>()))?;
+        let result = scope(self, &mut resource);
+        self.resources.insert(resource);
+        Ok(result)
+    }
+
     pub fn get_resource<T: 'static>(&self) -> Option<&T> {
         self.resources.get::<T>()
     }
@@ -81,69 +246,83 @@ impl<'a> ECS<'a> {
         self.resources.get_mut::<T>()
     }
 
+    /// Adds a resource that isn't `Send`, like the window. It can only be reached from the
+    /// thread that created the world
+    pub fn add_non_send_resource<T: 'static>(&mut self, resource: T) {
+        self.non_send.insert(resource)
+    }
+
+    pub fn remove_non_send_resource<T: 'static>(&mut self) -> Option<T> {
+        self.non_send.remove::<T>()
+    }
+
+    pub fn contains_non_send_resource<T: 'static>(&self) -> bool {
+        self.non_send.contains::<T>()
+    }
+
+    pub fn get_non_send_resource<T: 'static>(&self) -> Option<&T> {
+        self.non_send.get::<T>()
+    }
+
+    pub fn get_mut_non_send_resource<T: 'static>(&mut self) -> Option<&mut T> {
+        self.non_send.get_mut::<T>()
+    }
+
+    /// `resource_scope` for non-send resources
+    pub fn non_send_resource_scope<T, R>(
+        &mut self,
+        scope: impl FnOnce(&mut ECS<'a>, &mut T) -> R,
+    ) -> Result<R, EcsError>
+    where
+        T: 'static,
+    {
+        let mut resource = self
+            .non_send
+            .remove::<T>()
+            .ok_or(EcsError::MissingResource(type_name::<T>()))?;
+        let result = scope(self, &mut resource);
+        self.non_send.insert(resource);
+        Ok(result)
+    }
+
+    /// Creates the bundle's columns up front. Optional, `add_bundle` registers whatever is
+    /// missing itself, this just keeps that off the first spawn
     pub fn register_bundle<T>(&mut self)
     where
         T: Bundle + 'static,
     {
         T::register(&mut self.store);
-        self.bundles.insert(TypeId::of::<T>(), ());
     }
 
-    pub fn add_bundle<T>(&mut self, bundle: T) -> Option<Entity>
+    pub fn add_bundle<T>(&mut self, bundle: T) -> Result<Entity, EcsError>
     where
         T: Bundle + 'static,
     {
-        bundle.add_data(&mut self.store, &self.bundles)
+        let result = bundle.add_data(&mut self.store);
+        self.flush_hooks();
+        result
     }
-}
 
-//
-// PARKING FOR NOW (SKILL ISSUED)
-//
-// Get specific component maps eg get position, physics, size
-// Q needs to be an implementation of query data
-// get component gets it with type id of T as long as T is bound by component trait
-// Query needs needs many impls for varying lengths of tuples
-// They cannot be typeids they must be generic
-// query cannot do the querying
-
-// pub struct Query<Q: QueryData> {
-//     pub data: Q,
-// }
-//
-// impl<Q: QueryData> Query<Q> {
-//     pub fn fetch(ecs: ECS) -> Q {
-//         todo!()
-//     }
-// }
-//
-// trait QueryData {
-//     type Data: ComponentData;
-//     pub fn get_data(ecs: ECS) -> Self
-//     where
-//         Self: Sized;
-// }
-//
-// trait ComponentData {}
-//
-// impl<T1, T2> ComponentData for (T1, T2)
-// where
-//     T1: Component,
-//     T2: Component,
-// {
-// }
-//
-// impl<T1, T2> QueryData for (T1, T2)
-// where
-//     T1: Component,
-//     T2: Component,
-// {
-//     type Data = (T1, T2);
-//
-//     pub fn get_data(ecs: ECS) -> (Ref<'_, EntityMap<Entity, T1>>, Ref<'_, EntityMap<Entity, T2>>)
-//     where
-//         Self: Sized,
-//     {
-//
-//     }
-// }
+    /// Adds every component in the bundle to a live entity, replacing any it already has
+    pub fn insert_bundle<T>(&mut self, entity: Entity, bundle: T) -> Result<(), EcsError>
+    where
+        T: Bundle + 'static,
+    {
+        self.store.check_alive(entity)?;
+        T::register(&mut self.store);
+        let result = bundle.insert_into(&mut self.store, entity);
+        self.flush_hooks();
+        result
+    }
+
+    /// Strips the bundle's components off the entity, skipping any it doesn't have
+    pub fn remove_bundle<T>(&mut self, entity: Entity) -> Result<(), EcsError>
+    where
+        T: Bundle + 'static,
+    {
+        self.store.check_alive(entity)?;
+        T::remove_from(&mut self.store, entity);
+        self.flush_hooks();
+        Ok(())
+    }
+}
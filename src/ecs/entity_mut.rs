@@ -0,0 +1,148 @@
+use super::{Bundle, Component, EcsError, Entity, Lifecycle, Mut, ECS};
+
+/// Exclusive access to one live entity, for building it up or changing it a call at a
+/// time. Columns are registered on first use so nothing has to be set up beforehand.
+/// Hooks and observers can despawn the entity midway, after that the remaining calls do
+/// nothing
+pub struct EntityMut<'e, 'a> {
+    ecs: &'e mut ECS<'a>,
+    entity: Entity,
+}
+
+impl<'e, 'a> EntityMut<'e, 'a> {
+    pub fn id(&self) -> Entity {
+        self.entity
+    }
+
+    /// False once a hook or observer has despawned the entity
+    pub fn is_alive(&self) -> bool {
+        self.ecs.store.contains(self.entity)
+    }
+
+    /// Same as `insert`, reads better straight after `ECS::spawn`
+    pub fn with<T>(&mut self, component: T) -> &mut Self
+    where
+        T: Component + 'static,
+    {
+        self.insert(component)
+    }
+
+    /// Adds the component, replacing the one already there
+    pub fn insert<T>(&mut self, component: T) -> &mut Self
+    where
+        T: Component + 'static,
+    {
+        if !self.is_alive() {
+            return self;
+        }
+        self.ecs.store.try_register::<T>();
+        self.ecs
+            .store
+            .insert(self.entity, component)
+            .expect("the entity was checked to be alive");
+        self.ecs.flush_hooks();
+        self
+    }
+
+    pub fn insert_bundle<B>(&mut self, bundle: B) -> &mut Self
+    where
+        B: Bundle + 'static,
+    {
+        if self.is_alive() {
+            self.ecs
+                .insert_bundle(self.entity, bundle)
+                .expect("the entity was checked to be alive");
+        }
+        self
+    }
+
+    /// Drops the component if the entity has it
+    pub fn remove<T>(&mut self) -> &mut Self
+    where
+        T: Component + 'static,
+    {
+        self.ecs.store.discard::<T>(self.entity);
+        self.ecs.flush_hooks();
+        self
+    }
+
+    pub fn remove_bundle<B>(&mut self) -> &mut Self
+    where
+        B: Bundle + 'static,
+    {
+        if self.is_alive() {
+            self.ecs
+                .remove_bundle::<B>(self.entity)
+                .expect("the entity was checked to be alive");
+        }
+        self
+    }
+
+    /// Takes the component out, if the entity has it
+    pub fn take<T>(&mut self) -> Option<T>
+    where
+        T: Component + 'static,
+    {
+        self.ecs.remove_component::<T>(self.entity).ok()
+    }
+
+    /// Runs `observer` whenever `T` goes through `lifecycle` on this entity
+    pub fn observe<T>(
+        &mut self,
+        lifecycle: Lifecycle,
+        observer: impl FnMut(&mut ECS, Entity) + Send + Sync + 'static,
+    ) -> &mut Self
+    where
+        T: Component + 'static,
+    {
+        if self.is_alive() {
+            self.ecs
+                .observe::<T>(self.entity, lifecycle, observer)
+                .expect("the entity was checked to be alive");
+        }
+        self
+    }
+
+    pub fn get<T>(&self) -> Option<&T>
+    where
+        T: Component + 'static,
+    {
+        self.ecs.query::<T>(self.entity).ok()
+    }
+
+    pub fn get_mut<T>(&mut self) -> Option<Mut<'_, T>>
+    where
+        T: Component + 'static,
+    {
+        self.ecs.query_mut::<T>(self.entity).ok()
+    }
+
+    pub fn contains<T>(&self) -> bool
+    where
+        T: Component + 'static,
+    {
+        self.get::<T>().is_some()
+    }
+
+    pub fn despawn(self) {
+        if self.is_alive() {
+            self.ecs
+                .despawn(self.entity)
+                .expect("the entity was checked to be alive");
+        }
+    }
+}
+
+impl<'a> ECS<'a> {
+    /// Creates an empty entity and hands it back for adding components,
+    /// `ecs.spawn().with(Position::new(0, 0)).id()`
+    pub fn spawn(&mut self) -> EntityMut<'_, 'a> {
+        let entity = self.create_entity();
+        EntityMut { ecs: self, entity }
+    }
+
+    pub fn entity_mut(&mut self, entity: Entity) -> Result<EntityMut<'_, 'a>, EcsError> {
+        self.store.check_alive(entity)?;
+        Ok(EntityMut { ecs: self, entity })
+    }
+}
@@ -0,0 +1,45 @@
+use std::fmt;
+
+use super::Entity;
+
+/// Everything that can go wrong when touching the world through `ECS`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EcsError {
+    /// The component's column was never registered with the store
+    UnregisteredComponent(&'static str),
+    /// The entity was despawned or never existed
+    DeadEntity(Entity),
+    /// The entity is alive but doesn't have the component
+    MissingComponent {
+        entity: Entity,
+        component: &'static str,
+    },
+    /// The resource was never added, or is out in a `resource_scope`
+    MissingResource(&'static str),
+    /// Attaching `child` to `parent` would make `child` its own ancestor
+    HierarchyCycle { child: Entity, parent: Entity },
+}
+
+impl fmt::Display for EcsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EcsError::UnregisteredComponent(component) => {
+                write!(f, "component {} is not registered", component)
+            }
+            EcsError::DeadEntity(entity) => write!(f, "entity {:?} does not exist", entity),
+            EcsError::MissingComponent { entity, component } => {
+                write!(f, "entity {:?} has no {} component", entity, component)
+            }
+            EcsError::MissingResource(resource) => {
+                write!(f, "resource {} does not exist", resource)
+            }
+            EcsError::HierarchyCycle { child, parent } => write!(
+                f,
+                "can't attach {:?} to {:?}, it is already above it",
+                child, parent
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EcsError {}
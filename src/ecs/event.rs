@@ -0,0 +1,173 @@
+use std::any::type_name;
+
+use super::{AccessId, ResMut, SystemAccess, SystemParam, Tick, ECS};
+
+/// A double buffered queue of events. Events sent since the last `update` sit in `current`,
+/// the ones before that in `previous`, so every reader gets a full update cycle to see an
+/// event before it is dropped.
+pub struct Events<T> {
+    previous: Vec<T>,
+    current: Vec<T>,
+    // Ids of the first event in each buffer, ids are just a running count of sent events
+    previous_start: usize,
+    current_start: usize,
+    event_count: usize,
+}
+
+impl<T> Default for Events<T> {
+    fn default() -> Self {
+        Self {
+            previous: Vec::new(),
+            current: Vec::new(),
+            previous_start: 0,
+            current_start: 0,
+            event_count: 0,
+        }
+    }
+}
+
+impl<T> Events<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn send(&mut self, event: T) {
+        self.current.push(event);
+        self.event_count += 1;
+    }
+
+    /// Drops the older buffer and starts a new one, call once per update
+    pub fn update(&mut self) {
+        std::mem::swap(&mut self.previous, &mut self.current);
+        self.current.clear();
+        self.previous_start = self.current_start;
+        self.current_start = self.event_count;
+    }
+
+    /// Runs `update` on the `Events<T>` resource, added to the schedule by `App::add_event`
+    pub fn update_system(mut events: ResMut<Events<T>>)
+    where
+        T: Send + Sync + 'static,
+    {
+        events.update();
+    }
+
+    pub fn len(&self) -> usize {
+        self.previous.len() + self.current.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Every event still buffered with an id of at least `cursor`
+    pub fn read_from(&self, cursor: usize) -> impl Iterator<Item = &T> + '_ {
+        let previous = cursor.saturating_sub(self.previous_start);
+        let current = cursor.saturating_sub(self.current_start);
+        self.previous
+            .iter()
+            .skip(previous)
+            .chain(self.current.iter().skip(current))
+    }
+
+    /// The id the next event sent will get
+    pub fn event_count(&self) -> usize {
+        self.event_count
+    }
+}
+
+/// Sends events of type `T` from a system
+pub struct EventWriter<'w, T> {
+    events: &'w mut Events<T>,
+}
+
+impl<'w, T> EventWriter<'w, T> {
+    pub fn send(&mut self, event: T) {
+        self.events.send(event);
+    }
+}
+
+unsafe impl<'a, T: Send + Sync + 'static> SystemParam for EventWriter<'a, T> {
+    type State = ();
+    type Item<'w> = EventWriter<'w, T>;
+
+    fn init_state(_ecs: &mut ECS) {}
+
+    fn update_access(access: &mut SystemAccess) {
+        access.resources.add_write(AccessId::of::<Events<T>>());
+    }
+
+    unsafe fn get_param<'w>(
+        _state: &'w mut (),
+        ecs: &'w ECS<'w>,
+        _last_run: Tick,
+        _this_run: Tick,
+    ) -> EventWriter<'w, T> {
+        let events = ecs
+            .resources
+            .get_unchecked_mut::<Events<T>>()
+            .unwrap_or_else(|| panic!("events {} were never added", type_name::<T>()));
+        EventWriter { events }
+    }
+}
+
+/// How far a single `EventReader` has read, each system has its own
+#[derive(Default)]
+pub struct EventCursor {
+    last_event_count: usize,
+}
+
+/// Reads the events of type `T` this system hasn't seen yet
+pub struct EventReader<'w, T> {
+    cursor: &'w mut EventCursor,
+    events: &'w Events<T>,
+}
+
+impl<'w, T> EventReader<'w, T> {
+    pub fn read(&mut self) -> impl Iterator<Item = &T> + '_ {
+        let from = self.cursor.last_event_count;
+        self.cursor.last_event_count = self.events.event_count();
+        self.events.read_from(from)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events
+            .read_from(self.cursor.last_event_count)
+            .next()
+            .is_none()
+    }
+
+    /// Skips everything unread
+    pub fn clear(&mut self) {
+        self.cursor.last_event_count = self.events.event_count();
+    }
+}
+
+unsafe impl<'a, T: Send + Sync + 'static> SystemParam for EventReader<'a, T> {
+    type State = EventCursor;
+    type Item<'w> = EventReader<'w, T>;
+
+    fn init_state(_ecs: &mut ECS) -> EventCursor {
+        EventCursor::default()
+    }
+
+    fn update_access(access: &mut SystemAccess) {
+        access.resources.add_read(AccessId::of::<Events<T>>());
+    }
+
+    unsafe fn get_param<'w>(
+        state: &'w mut EventCursor,
+        ecs: &'w ECS<'w>,
+        _last_run: Tick,
+        _this_run: Tick,
+    ) -> EventReader<'w, T> {
+        let events = ecs
+            .resources
+            .get::<Events<T>>()
+            .unwrap_or_else(|| panic!("events {} were never added", type_name::<T>()));
+        EventReader {
+            cursor: state,
+            events,
+        }
+    }
+}
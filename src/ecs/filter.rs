@@ -0,0 +1,243 @@
+use std::marker::PhantomData;
+
+use super::{
+    smallest_set, Access, AccessId, ColumnFetch, Component, ComponentStorage, Entity, EntitySet,
+    Tick,
+};
+
+/// Narrows down which entities a `Query` visits without fetching any data.
+///
+/// `With`, `Without`, `Added` and `Changed` can be combined in a tuple, where every filter has
+/// to pass, or in an `Or`, where any of them can.
+pub trait QueryFilter {
+    type Fetch<'w>;
+
+    fn update_access(access: &mut Access);
+
+    fn init_fetch<'w>(
+        store: &'w ComponentStorage<'w>,
+        last_run: Tick,
+        this_run: Tick,
+    ) -> Self::Fetch<'w>;
+
+    fn candidates<'f>(fetch: &'f Self::Fetch<'_>) -> Option<&'f dyn EntitySet>;
+
+    fn filter(fetch: &Self::Fetch<'_>, entity: Entity) -> bool;
+}
+
+/// Entities that have a `T`, without borrowing it
+pub struct With<T>(PhantomData<T>);
+
+/// Entities that don't have a `T`
+pub struct Without<T>(PhantomData<T>);
+
+/// Entities where `T` was inserted since the query last ran
+pub struct Added<T>(PhantomData<T>);
+
+/// Entities where `T` was inserted or mutably accessed since the query last ran
+pub struct Changed<T>(PhantomData<T>);
+
+/// Entities that pass any of the filters in the tuple
+pub struct Or<T>(PhantomData<T>);
+
+impl<T> QueryFilter for With<T>
+where
+    T: Component + 'static,
+{
+    type Fetch<'w> = ColumnFetch<'w, T>;
+
+    fn update_access(_access: &mut Access) {}
+
+    fn init_fetch<'w>(
+        store: &'w ComponentStorage<'w>,
+        last_run: Tick,
+        this_run: Tick,
+    ) -> Self::Fetch<'w> {
+        ColumnFetch::new(store, last_run, this_run)
+    }
+
+    fn candidates<'f>(fetch: &'f Self::Fetch<'_>) -> Option<&'f dyn EntitySet> {
+        Some(fetch.candidates())
+    }
+
+    fn filter(fetch: &Self::Fetch<'_>, entity: Entity) -> bool {
+        fetch.contains(entity)
+    }
+}
+
+impl<T> QueryFilter for Without<T>
+where
+    T: Component + 'static,
+{
+    type Fetch<'w> = ColumnFetch<'w, T>;
+
+    fn update_access(_access: &mut Access) {}
+
+    fn init_fetch<'w>(
+        store: &'w ComponentStorage<'w>,
+        last_run: Tick,
+        this_run: Tick,
+    ) -> Self::Fetch<'w> {
+        ColumnFetch::new(store, last_run, this_run)
+    }
+
+    fn candidates<'f>(_fetch: &'f Self::Fetch<'_>) -> Option<&'f dyn EntitySet> {
+        None
+    }
+
+    fn filter(fetch: &Self::Fetch<'_>, entity: Entity) -> bool {
+        !fetch.contains(entity)
+    }
+}
+
+impl<T> QueryFilter for Added<T>
+where
+    T: Component + 'static,
+{
+    type Fetch<'w> = ColumnFetch<'w, T>;
+
+    // Reading the ticks while someone else stamps them counts as a read of `T`
+    fn update_access(access: &mut Access) {
+        access.add_read(AccessId::of::<T>());
+    }
+
+    fn init_fetch<'w>(
+        store: &'w ComponentStorage<'w>,
+        last_run: Tick,
+        this_run: Tick,
+    ) -> Self::Fetch<'w> {
+        ColumnFetch::new(store, last_run, this_run)
+    }
+
+    fn candidates<'f>(fetch: &'f Self::Fetch<'_>) -> Option<&'f dyn EntitySet> {
+        Some(fetch.candidates())
+    }
+
+    fn filter(fetch: &Self::Fetch<'_>, entity: Entity) -> bool {
+        fetch
+            .column
+            .and_then(|column| column.get_ticks(entity))
+            .is_some_and(|ticks| ticks.is_added(fetch.last_run, fetch.this_run))
+    }
+}
+
+impl<T> QueryFilter for Changed<T>
+where
+    T: Component + 'static,
+{
+    type Fetch<'w> = ColumnFetch<'w, T>;
+
+    fn update_access(access: &mut Access) {
+        access.add_read(AccessId::of::<T>());
+    }
+
+    fn init_fetch<'w>(
+        store: &'w ComponentStorage<'w>,
+        last_run: Tick,
+        this_run: Tick,
+    ) -> Self::Fetch<'w> {
+        ColumnFetch::new(store, last_run, this_run)
+    }
+
+    fn candidates<'f>(fetch: &'f Self::Fetch<'_>) -> Option<&'f dyn EntitySet> {
+        Some(fetch.candidates())
+    }
+
+    fn filter(fetch: &Self::Fetch<'_>, entity: Entity) -> bool {
+        fetch
+            .column
+            .and_then(|column| column.get_ticks(entity))
+            .is_some_and(|ticks| ticks.is_changed(fetch.last_run, fetch.this_run))
+    }
+}
+
+impl QueryFilter for () {
+    type Fetch<'w> = ();
+
+    fn update_access(_access: &mut Access) {}
+
+    fn init_fetch<'w>(
+        _store: &'w ComponentStorage<'w>,
+        _last_run: Tick,
+        _this_run: Tick,
+    ) -> Self::Fetch<'w> {
+    }
+
+    fn candidates<'f>(_fetch: &'f Self::Fetch<'_>) -> Option<&'f dyn EntitySet> {
+        None
+    }
+
+    fn filter(_fetch: &Self::Fetch<'_>, _entity: Entity) -> bool {
+        true
+    }
+}
+
+macro_rules! impl_query_filter_tuple {
+    ($($name:ident),*) => {
+        #[allow(non_snake_case)]
+        impl<$($name: QueryFilter),*> QueryFilter for ($($name,)*) {
+            type Fetch<'w> = ($($name::Fetch<'w>,)*);
+
+            fn update_access(access: &mut Access) {
+                $($name::update_access(access);)*
+            }
+
+            fn init_fetch<'w>(
+                store: &'w ComponentStorage<'w>,
+                last_run: Tick,
+                this_run: Tick,
+            ) -> Self::Fetch<'w> {
+                ($($name::init_fetch(store, last_run, this_run),)*)
+            }
+
+            fn candidates<'f>(fetch: &'f Self::Fetch<'_>) -> Option<&'f dyn EntitySet> {
+                let ($($name,)*) = fetch;
+                let mut smallest: Option<&'f dyn EntitySet> = None;
+                $(
+                    smallest = smallest_set(smallest, $name::candidates($name));
+                )*
+                smallest
+            }
+
+            fn filter(fetch: &Self::Fetch<'_>, entity: Entity) -> bool {
+                let ($($name,)*) = fetch;
+                true $(&& $name::filter($name, entity))*
+            }
+        }
+
+        #[allow(non_snake_case)]
+        impl<$($name: QueryFilter),*> QueryFilter for Or<($($name,)*)> {
+            type Fetch<'w> = ($($name::Fetch<'w>,)*);
+
+            fn update_access(access: &mut Access) {
+                $($name::update_access(access);)*
+            }
+
+            fn init_fetch<'w>(
+                store: &'w ComponentStorage<'w>,
+                last_run: Tick,
+                this_run: Tick,
+            ) -> Self::Fetch<'w> {
+                ($($name::init_fetch(store, last_run, this_run),)*)
+            }
+
+            fn candidates<'f>(_fetch: &'f Self::Fetch<'_>) -> Option<&'f dyn EntitySet> {
+                None
+            }
+
+            fn filter(fetch: &Self::Fetch<'_>, entity: Entity) -> bool {
+                let ($($name,)*) = fetch;
+                false $(|| $name::filter($name, entity))*
+            }
+        }
+    };
+}
+
+impl_query_filter_tuple!(A);
+impl_query_filter_tuple!(A, B);
+impl_query_filter_tuple!(A, B, C);
+impl_query_filter_tuple!(A, B, C, D);
+impl_query_filter_tuple!(A, B, C, D, E);
+impl_query_filter_tuple!(A, B, C, D, E, F);
+impl_query_filter_tuple!(A, B, C, D, E, F, G);
+impl_query_filter_tuple!(A, B, C, D, E, F, G, H);
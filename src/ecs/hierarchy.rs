@@ -0,0 +1,130 @@
+use super::{Component, EcsError, Entity, Reflect, ECS};
+
+/// The entity this one is attached to. Only `ECS::set_parent` hands these out so it always
+/// agrees with the parent's `Children`. Sparse like `Children`, only the few entities in a
+/// tree pay for a slot
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect, Component)]
+#[component(storage = "sparse")]
+pub struct Parent(Entity);
+
+impl Parent {
+    pub fn get(&self) -> Entity {
+        self.0
+    }
+}
+
+/// Every entity attached to this one, in the order they were attached
+#[derive(Clone, Debug, Default, PartialEq, Eq, Reflect, Component)]
+#[component(storage = "sparse")]
+pub struct Children(Vec<Entity>);
+
+impl Children {
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.0.iter().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<'a> ECS<'a> {
+    /// Attaches `child` to `parent`, moving it off whatever it was attached to before
+    pub fn set_parent(&mut self, child: Entity, parent: Entity) -> Result<(), EcsError> {
+        self.store.check_alive(child)?;
+        self.store.check_alive(parent)?;
+        if self.is_ancestor(child, parent) {
+            return Err(EcsError::HierarchyCycle { child, parent });
+        }
+        self.unlink_parent(child)?;
+
+        self.store.insert(child, Parent(parent))?;
+        let tick = self.store.change_tick();
+        match self.store.get_mut::<Children>()?.get_mut(parent, tick) {
+            Some(mut children) => children.0.push(child),
+            None => {
+                self.store.insert(parent, Children(vec![child]))?;
+            }
+        }
+        self.flush_hooks();
+        Ok(())
+    }
+
+    /// Detaches `child` from its parent, if it has one
+    pub fn remove_parent(&mut self, child: Entity) -> Result<(), EcsError> {
+        self.store.check_alive(child)?;
+        self.unlink_parent(child)?;
+        self.flush_hooks();
+        Ok(())
+    }
+
+    // `remove_parent` without running hooks, for callers still partway through a change.
+    // Goes through `discard` so `Parent` and `Children` removals still queue their hooks
+    fn unlink_parent(&mut self, child: Entity) -> Result<(), EcsError> {
+        let Some(&Parent(parent)) = self.store.get::<Parent>()?.get(child) else {
+            return Ok(());
+        };
+        self.store.discard::<Parent>(child);
+        let tick = self.store.change_tick();
+        let last_child = match self.store.get_mut::<Children>()?.get_mut(parent, tick) {
+            Some(mut siblings) => {
+                siblings.0.retain(|sibling| *sibling != child);
+                siblings.is_empty()
+            }
+            None => false,
+        };
+        if last_child {
+            self.store.discard::<Children>(parent);
+        }
+        Ok(())
+    }
+
+    /// Whether `ancestor` is `entity` itself or somewhere above it
+    pub fn is_ancestor(&self, ancestor: Entity, entity: Entity) -> bool {
+        let Ok(parents) = self.store.get::<Parent>() else {
+            return ancestor == entity;
+        };
+        let mut current = Some(entity);
+        while let Some(entity) = current {
+            if entity == ancestor {
+                return true;
+            }
+            current = parents.get(entity).map(Parent::get);
+        }
+        false
+    }
+
+    /// Despawns the entity along with everything attached below it
+    pub fn despawn_recursive(&mut self, entity: Entity) -> Result<(), EcsError> {
+        self.store.check_alive(entity)?;
+        let mut subtree = vec![entity];
+        let mut i = 0;
+        while i < subtree.len() {
+            if let Some(children) = self.store.get::<Children>()?.get(subtree[i]) {
+                subtree.extend(children.iter());
+            }
+            i += 1;
+        }
+        // Leaves first so nothing is left pointing at an entity that's already gone
+        for entity in subtree.into_iter().rev() {
+            self.despawn(entity)?;
+        }
+        Ok(())
+    }
+
+    // Called by `despawn`, children outlive their parent as new roots
+    pub(crate) fn detach(&mut self, entity: Entity) -> Result<(), EcsError> {
+        self.unlink_parent(entity)?;
+        // Nothing to remove is the only way this fails, `despawn` checked the entity
+        if let Ok(children) = self.store.remove::<Children>(entity) {
+            for child in children.iter() {
+                self.store.discard::<Parent>(child);
+            }
+        }
+        Ok(())
+    }
+}
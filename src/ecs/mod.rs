@@ -1,8 +1,41 @@
+#[allow(clippy::module_inception)]
 pub mod ecs;
 pub use ecs::*;
 pub mod component;
 pub use component::*;
 pub mod resource;
 pub use resource::*;
+pub mod non_send;
+pub use non_send::*;
 pub mod bundle;
 pub use bundle::*;
+pub mod query;
+pub use query::*;
+pub mod access;
+pub use access::*;
+pub mod tick;
+pub use tick::*;
+pub mod filter;
+pub use filter::*;
+pub mod system;
+pub use system::*;
+pub mod schedule;
+pub use schedule::*;
+pub mod command;
+pub use command::*;
+pub mod archetype;
+pub use archetype::*;
+pub mod event;
+pub use event::*;
+pub mod error;
+pub use error::*;
+pub mod hierarchy;
+pub use hierarchy::*;
+pub mod scene;
+pub use scene::*;
+pub mod reflect;
+pub use reflect::*;
+pub mod entity_mut;
+pub use entity_mut::*;
+pub mod observer;
+pub use observer::*;
@@ -0,0 +1,186 @@
+use std::{
+    any::{type_name, Any, TypeId},
+    cell::UnsafeCell,
+    collections::HashMap,
+    ops::{Deref, DerefMut},
+    thread::{self, ThreadId},
+};
+
+use super::{AccessId, SystemAccess, SystemParam, Tick, ECS};
+
+/// Resources that can't leave the thread they were made on, like the window or the GPU
+/// surface. The storage remembers the thread that created it and panics if anything else
+/// touches a value, the scheduler keeps systems that use them on that thread
+pub struct NonSendStorage {
+    storage: HashMap<TypeId, UnsafeCell<Box<dyn Any>>>,
+    main_thread: ThreadId,
+}
+
+// SAFETY: the world is shared with worker threads for parallel systems, every access to a
+// value checks it's happening on `main_thread` so nothing here is ever used anywhere else
+unsafe impl Send for NonSendStorage {}
+unsafe impl Sync for NonSendStorage {}
+
+impl Default for NonSendStorage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NonSendStorage {
+    pub fn new() -> Self {
+        Self {
+            storage: HashMap::new(),
+            main_thread: thread::current().id(),
+        }
+    }
+
+    pub fn is_main_thread(&self) -> bool {
+        thread::current().id() == self.main_thread
+    }
+
+    fn check_thread<T>(&self) {
+        assert!(
+            self.is_main_thread(),
+            "non-send resource {} accessed off the main thread",
+            type_name::<T>()
+        );
+    }
+
+    pub fn insert<T: 'static>(&mut self, resource: T) {
+        self.check_thread::<T>();
+        self.storage
+            .insert(TypeId::of::<T>(), UnsafeCell::new(Box::new(resource)));
+    }
+
+    pub fn get<T: 'static>(&self) -> Option<&T> {
+        self.check_thread::<T>();
+        let resource_ref = self.storage.get(&TypeId::of::<T>())?;
+        unsafe { &*resource_ref.get() }.downcast_ref::<T>()
+    }
+
+    pub fn get_mut<T: 'static>(&mut self) -> Option<&mut T> {
+        self.check_thread::<T>();
+        let resource_ref = self.storage.get_mut(&TypeId::of::<T>())?;
+        resource_ref.get_mut().downcast_mut::<T>()
+    }
+
+    pub fn remove<T: 'static>(&mut self) -> Option<T> {
+        self.check_thread::<T>();
+        let resource_ref = self.storage.remove(&TypeId::of::<T>())?;
+        resource_ref
+            .into_inner()
+            .downcast::<T>()
+            .ok()
+            .map(|resource| *resource)
+    }
+
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.storage.contains_key(&TypeId::of::<T>())
+    }
+
+    /// # Safety
+    /// Nothing else may reference the resource for the returned lifetime.
+    #[allow(clippy::mut_from_ref)]
+    pub unsafe fn get_unchecked_mut<T: 'static>(&self) -> Option<&mut T> {
+        self.check_thread::<T>();
+        let resource_ref = self.storage.get(&TypeId::of::<T>())?;
+        (*resource_ref.get()).downcast_mut::<T>()
+    }
+}
+
+impl Drop for NonSendStorage {
+    fn drop(&mut self) {
+        // Already unwinding, a second panic would abort before the first one is reported
+        if !self.storage.is_empty() && !thread::panicking() {
+            assert!(
+                self.is_main_thread(),
+                "non-send resources dropped off the main thread"
+            );
+        }
+    }
+}
+
+/// Shared access to a non-send resource from a system, which then always runs on the main
+/// thread
+pub struct NonSend<'w, T> {
+    value: &'w T,
+}
+
+impl<'w, T> Deref for NonSend<'w, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+/// Exclusive access to a non-send resource from a system, which then always runs on the
+/// main thread
+pub struct NonSendMut<'w, T> {
+    value: &'w mut T,
+}
+
+impl<'w, T> Deref for NonSendMut<'w, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<'w, T> DerefMut for NonSendMut<'w, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value
+    }
+}
+
+unsafe impl<'a, T: 'static> SystemParam for NonSend<'a, T> {
+    type State = ();
+    type Item<'w> = NonSend<'w, T>;
+
+    fn init_state(_ecs: &mut ECS) {}
+
+    fn update_access(access: &mut SystemAccess) {
+        access.non_send.add_read(AccessId::of::<T>());
+        access.main_thread = true;
+    }
+
+    unsafe fn get_param<'w>(
+        _state: &'w mut (),
+        ecs: &'w ECS<'w>,
+        _last_run: Tick,
+        _this_run: Tick,
+    ) -> NonSend<'w, T> {
+        let value = ecs
+            .non_send
+            .get::<T>()
+            .unwrap_or_else(|| panic!("non-send resource {} does not exist", type_name::<T>()));
+        NonSend { value }
+    }
+}
+
+unsafe impl<'a, T: 'static> SystemParam for NonSendMut<'a, T> {
+    type State = ();
+    type Item<'w> = NonSendMut<'w, T>;
+
+    fn init_state(_ecs: &mut ECS) {}
+
+    fn update_access(access: &mut SystemAccess) {
+        access.non_send.add_write(AccessId::of::<T>());
+        access.main_thread = true;
+    }
+
+    unsafe fn get_param<'w>(
+        _state: &'w mut (),
+        ecs: &'w ECS<'w>,
+        _last_run: Tick,
+        _this_run: Tick,
+    ) -> NonSendMut<'w, T> {
+        let value = ecs
+            .non_send
+            .get_unchecked_mut::<T>()
+            .unwrap_or_else(|| panic!("non-send resource {} does not exist", type_name::<T>()));
+        NonSendMut { value }
+    }
+}
@@ -0,0 +1,141 @@
+use std::any::TypeId;
+
+use super::{Component, ComponentStorage, EcsError, Entity, ECS};
+
+/// The points in a component's life that hooks and observers run at
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Lifecycle {
+    /// Inserted on an entity that didn't have it
+    Add,
+    /// Inserted at all, whether it was added or replaced the old value
+    Insert,
+    /// Removed or despawned with its entity, the value is already gone by the time this runs
+    Remove,
+}
+
+/// Set up once per component type on `ComponentStorage`, e.g. to keep something outside
+/// the world in step with a component
+pub type ComponentHook = fn(&mut ECS, Entity);
+
+#[derive(Clone, Default)]
+pub struct ComponentHooks {
+    pub on_add: Vec<ComponentHook>,
+    pub on_insert: Vec<ComponentHook>,
+    pub on_remove: Vec<ComponentHook>,
+}
+
+impl ComponentHooks {
+    pub fn get(&self, lifecycle: Lifecycle) -> &[ComponentHook] {
+        match lifecycle {
+            Lifecycle::Add => &self.on_add,
+            Lifecycle::Insert => &self.on_insert,
+            Lifecycle::Remove => &self.on_remove,
+        }
+    }
+}
+
+// Queued by `ComponentStorage` as changes happen and run by `ECS::flush_hooks` once they're
+// done, so hooks always see the world in one piece
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Triggered {
+    pub lifecycle: Lifecycle,
+    pub component: TypeId,
+    pub entity: Entity,
+}
+
+pub type Observer = Box<dyn FnMut(&mut ECS, Entity) + Send + Sync>;
+
+struct ObserverEntry {
+    lifecycle: Lifecycle,
+    component: TypeId,
+    // `None` watches every entity
+    entity: Option<Entity>,
+    observer: Observer,
+}
+
+/// Closures run after hooks, for anyone watching a component without owning it. Ones
+/// attached to an entity go away when it's despawned
+#[derive(Default)]
+pub struct Observers {
+    observers: Vec<ObserverEntry>,
+}
+
+impl Observers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.observers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.observers.is_empty()
+    }
+
+    fn add(
+        &mut self,
+        lifecycle: Lifecycle,
+        component: TypeId,
+        entity: Option<Entity>,
+        observer: Observer,
+    ) {
+        self.observers.push(ObserverEntry {
+            lifecycle,
+            component,
+            entity,
+            observer,
+        });
+    }
+
+    pub(crate) fn run(&mut self, ecs: &mut ECS, triggered: Triggered) {
+        for entry in self.observers.iter_mut() {
+            if entry.lifecycle == triggered.lifecycle
+                && entry.component == triggered.component
+                && entry.entity.is_none_or(|entity| entity == triggered.entity)
+            {
+                (entry.observer)(ecs, triggered.entity);
+            }
+        }
+    }
+
+    pub(crate) fn append(&mut self, other: &mut Observers) {
+        self.observers.append(&mut other.observers);
+    }
+
+    pub(crate) fn retain_alive(&mut self, store: &ComponentStorage) {
+        self.observers
+            .retain(|entry| entry.entity.is_none_or(|entity| store.contains(entity)));
+    }
+}
+
+impl<'a> ECS<'a> {
+    /// Runs `observer` whenever `T` goes through `lifecycle` on any entity
+    pub fn add_observer<T: Component + 'static>(
+        &mut self,
+        lifecycle: Lifecycle,
+        observer: impl FnMut(&mut ECS, Entity) + Send + Sync + 'static,
+    ) {
+        self.store.watch(TypeId::of::<T>());
+        self.observers
+            .add(lifecycle, TypeId::of::<T>(), None, Box::new(observer));
+    }
+
+    /// Runs `observer` whenever `T` goes through `lifecycle` on `entity`, until it's despawned
+    pub fn observe<T: Component + 'static>(
+        &mut self,
+        entity: Entity,
+        lifecycle: Lifecycle,
+        observer: impl FnMut(&mut ECS, Entity) + Send + Sync + 'static,
+    ) -> Result<(), EcsError> {
+        self.store.check_alive(entity)?;
+        self.store.watch(TypeId::of::<T>());
+        self.observers.add(
+            lifecycle,
+            TypeId::of::<T>(),
+            Some(entity),
+            Box::new(observer),
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,534 @@
+use std::{any::type_name, fmt, marker::PhantomData};
+
+use super::{
+    Access, AccessId, Component, ComponentColumn, ComponentStorage, Entity, Mut, QueryFilter, Tick,
+    ECS,
+};
+
+/// Something that can be pulled out of the `ComponentStorage` for a single entity.
+///
+/// Implemented for `&T`, `&mut T`, `Option<Q>` and tuples of those, which is what lets a
+/// query be written as `Query<(&Position, &mut Velocity, Option<&Size>)>`.
+///
+/// # Safety
+/// `update_access` must report every component type `fetch` hands out, with `&mut` access
+/// reported as a write, otherwise the aliasing checks done by `Query` are meaningless.
+pub unsafe trait QueryData {
+    type Item<'w>;
+    type Fetch<'w>;
+    type ReadOnly: ReadOnlyQueryData;
+
+    fn update_access(access: &mut Access);
+
+    fn init_fetch<'w>(
+        store: &'w ComponentStorage<'w>,
+        last_run: Tick,
+        this_run: Tick,
+    ) -> Self::Fetch<'w>;
+
+    /// The smallest set of entities that could possibly match, used to drive iteration
+    fn candidates<'f>(fetch: &'f Self::Fetch<'_>) -> Option<&'f dyn EntitySet>;
+
+    fn matches(fetch: &Self::Fetch<'_>, entity: Entity) -> bool;
+
+    /// # Safety
+    /// `matches` must have returned true for `entity` and the caller must not hand out two
+    /// items for the same entity at once when `Self` writes.
+    unsafe fn fetch<'w>(fetch: &Self::Fetch<'w>, entity: Entity) -> Self::Item<'w>;
+}
+
+/// # Safety
+/// Only implement for query data that never hands out `&mut`.
+pub unsafe trait ReadOnlyQueryData: QueryData<ReadOnly = Self> {}
+
+pub trait EntitySet {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn entities(&self) -> Vec<Entity>;
+}
+
+impl<T> EntitySet for ComponentColumn<T> {
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn entities(&self) -> Vec<Entity> {
+        self.keys().collect()
+    }
+}
+
+struct NoEntities;
+
+impl EntitySet for NoEntities {
+    fn len(&self) -> usize {
+        0
+    }
+
+    fn entities(&self) -> Vec<Entity> {
+        Vec::new()
+    }
+}
+
+pub fn smallest_set<'f>(
+    current: Option<&'f dyn EntitySet>,
+    next: Option<&'f dyn EntitySet>,
+) -> Option<&'f dyn EntitySet> {
+    match (current, next) {
+        (Some(current), Some(next)) if next.len() < current.len() => Some(next),
+        (None, next) => next,
+        (current, _) => current,
+    }
+}
+
+/// A single column looked up once per query rather than once per entity
+pub struct ColumnFetch<'w, T> {
+    pub column: Option<&'w ComponentColumn<T>>,
+    pub last_run: Tick,
+    pub this_run: Tick,
+}
+
+impl<'w, T> ColumnFetch<'w, T>
+where
+    T: Component + 'static,
+{
+    pub fn new(store: &'w ComponentStorage<'w>, last_run: Tick, this_run: Tick) -> Self {
+        Self {
+            column: store.get::<T>().ok(),
+            last_run,
+            this_run,
+        }
+    }
+
+    pub fn candidates(&self) -> &dyn EntitySet {
+        match self.column {
+            Some(column) => column,
+            None => &NoEntities,
+        }
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.column
+            .is_some_and(|column| column.contains_key(entity))
+    }
+}
+
+unsafe impl<T> QueryData for &T
+where
+    T: Component + 'static,
+{
+    type Item<'w> = &'w T;
+    type Fetch<'w> = ColumnFetch<'w, T>;
+    type ReadOnly = Self;
+
+    fn update_access(access: &mut Access) {
+        let id = AccessId::of::<T>();
+        assert!(
+            !access.writes.contains(&id),
+            "&{} conflicts with a &mut {} in the same query",
+            id.name,
+            id.name
+        );
+        access.add_read(id);
+    }
+
+    fn init_fetch<'w>(
+        store: &'w ComponentStorage<'w>,
+        last_run: Tick,
+        this_run: Tick,
+    ) -> Self::Fetch<'w> {
+        ColumnFetch::new(store, last_run, this_run)
+    }
+
+    fn candidates<'f>(fetch: &'f Self::Fetch<'_>) -> Option<&'f dyn EntitySet> {
+        Some(fetch.candidates())
+    }
+
+    fn matches(fetch: &Self::Fetch<'_>, entity: Entity) -> bool {
+        fetch.contains(entity)
+    }
+
+    unsafe fn fetch<'w>(fetch: &Self::Fetch<'w>, entity: Entity) -> Self::Item<'w> {
+        fetch.column.unwrap().get(entity).unwrap()
+    }
+}
+
+unsafe impl<T> ReadOnlyQueryData for &T where T: Component + 'static {}
+
+unsafe impl<T> QueryData for &mut T
+where
+    T: Component + 'static,
+{
+    type Item<'w> = Mut<'w, T>;
+    type Fetch<'w> = ColumnFetch<'w, T>;
+    type ReadOnly = &'static T;
+
+    fn update_access(access: &mut Access) {
+        let id = AccessId::of::<T>();
+        assert!(
+            !access.reads.contains(&id) && !access.writes.contains(&id),
+            "&mut {} conflicts with another borrow of {} in the same query",
+            id.name,
+            id.name
+        );
+        access.add_write(id);
+    }
+
+    fn init_fetch<'w>(
+        store: &'w ComponentStorage<'w>,
+        last_run: Tick,
+        this_run: Tick,
+    ) -> Self::Fetch<'w> {
+        ColumnFetch::new(store, last_run, this_run)
+    }
+
+    fn candidates<'f>(fetch: &'f Self::Fetch<'_>) -> Option<&'f dyn EntitySet> {
+        Some(fetch.candidates())
+    }
+
+    fn matches(fetch: &Self::Fetch<'_>, entity: Entity) -> bool {
+        fetch.contains(entity)
+    }
+
+    unsafe fn fetch<'w>(fetch: &Self::Fetch<'w>, entity: Entity) -> Self::Item<'w> {
+        let (component, ticks) = fetch.column.unwrap().get_unchecked_mut(entity).unwrap();
+        Mut::new(component, ticks, fetch.this_run)
+    }
+}
+
+unsafe impl<Q> QueryData for Option<Q>
+where
+    Q: QueryData,
+{
+    type Item<'w> = Option<Q::Item<'w>>;
+    type Fetch<'w> = Q::Fetch<'w>;
+    type ReadOnly = Option<Q::ReadOnly>;
+
+    fn update_access(access: &mut Access) {
+        Q::update_access(access);
+    }
+
+    fn init_fetch<'w>(
+        store: &'w ComponentStorage<'w>,
+        last_run: Tick,
+        this_run: Tick,
+    ) -> Self::Fetch<'w> {
+        Q::init_fetch(store, last_run, this_run)
+    }
+
+    fn candidates<'f>(_fetch: &'f Self::Fetch<'_>) -> Option<&'f dyn EntitySet> {
+        None
+    }
+
+    fn matches(_fetch: &Self::Fetch<'_>, _entity: Entity) -> bool {
+        true
+    }
+
+    unsafe fn fetch<'w>(fetch: &Self::Fetch<'w>, entity: Entity) -> Self::Item<'w> {
+        if Q::matches(fetch, entity) {
+            Some(Q::fetch(fetch, entity))
+        } else {
+            None
+        }
+    }
+}
+
+unsafe impl<Q> ReadOnlyQueryData for Option<Q> where Q: ReadOnlyQueryData {}
+
+macro_rules! impl_query_data_tuple {
+    ($($name:ident),*) => {
+        #[allow(non_snake_case)]
+        unsafe impl<$($name: QueryData),*> QueryData for ($($name,)*) {
+            type Item<'w> = ($($name::Item<'w>,)*);
+            type Fetch<'w> = ($($name::Fetch<'w>,)*);
+            type ReadOnly = ($($name::ReadOnly,)*);
+
+            fn update_access(access: &mut Access) {
+                $($name::update_access(access);)*
+            }
+
+            fn init_fetch<'w>(
+                store: &'w ComponentStorage<'w>,
+                last_run: Tick,
+                this_run: Tick,
+            ) -> Self::Fetch<'w> {
+                ($($name::init_fetch(store, last_run, this_run),)*)
+            }
+
+            fn candidates<'f>(fetch: &'f Self::Fetch<'_>) -> Option<&'f dyn EntitySet> {
+                let ($($name,)*) = fetch;
+                let mut smallest: Option<&'f dyn EntitySet> = None;
+                $(
+                    smallest = smallest_set(smallest, $name::candidates($name));
+                )*
+                smallest
+            }
+
+            fn matches(fetch: &Self::Fetch<'_>, entity: Entity) -> bool {
+                let ($($name,)*) = fetch;
+                true $(&& $name::matches($name, entity))*
+            }
+
+            unsafe fn fetch<'w>(fetch: &Self::Fetch<'w>, entity: Entity) -> Self::Item<'w> {
+                let ($($name,)*) = fetch;
+                ($($name::fetch($name, entity),)*)
+            }
+        }
+
+        unsafe impl<$($name: ReadOnlyQueryData),*> ReadOnlyQueryData for ($($name,)*) {}
+    };
+}
+
+impl_query_data_tuple!(A);
+impl_query_data_tuple!(A, B);
+impl_query_data_tuple!(A, B, C);
+impl_query_data_tuple!(A, B, C, D);
+impl_query_data_tuple!(A, B, C, D, E);
+impl_query_data_tuple!(A, B, C, D, E, F);
+impl_query_data_tuple!(A, B, C, D, E, F, G);
+impl_query_data_tuple!(A, B, C, D, E, F, G, H);
+
+/// Iterates every entity that has all the components `Q` asks for and passes the filter `F`.
+///
+/// ```ignore
+/// let mut query = Query::<(&Gravity, &mut Velocity), Without<InputState>>::new(&mut ecs);
+/// for (entity, (gravity, mut velocity)) in query.iter_mut() {
+///     velocity.vy += gravity.gy as i32;
+/// }
+/// ```
+pub struct Query<'w, Q: QueryData, F: QueryFilter = ()> {
+    store: &'w ComponentStorage<'w>,
+    last_run: Tick,
+    this_run: Tick,
+    _marker: PhantomData<(Q, F)>,
+}
+
+impl<'w, Q: QueryData, F: QueryFilter> Query<'w, Q, F> {
+    pub fn new(ecs: &'w mut ECS) -> Self {
+        Self::access();
+        // SAFETY: the world is borrowed exclusively and the access was just validated
+        unsafe { Self::new_unchecked(&ecs.store) }
+    }
+
+    /// A query that can only read, so several of them can be alive at once
+    pub fn read_only(ecs: &'w ECS) -> Self
+    where
+        Q: ReadOnlyQueryData,
+    {
+        // SAFETY: read only data can't alias
+        unsafe { Self::new_unchecked(&ecs.store) }
+    }
+
+    /// A read only query whose `Added`/`Changed` filters look back to `last_run` instead of the
+    /// last `clear_trackers`, for code outside the schedule that keeps its own tick
+    pub fn read_only_since(ecs: &'w ECS, last_run: Tick) -> Self
+    where
+        Q: ReadOnlyQueryData,
+    {
+        // SAFETY: read only data can't alias
+        unsafe { Self::with_ticks(&ecs.store, last_run, ecs.store.change_tick()) }
+    }
+
+    /// # Safety
+    /// Nothing else may access the components `Q` writes for as long as the query is alive.
+    pub unsafe fn new_unchecked(store: &'w ComponentStorage<'w>) -> Self {
+        Self::with_ticks(store, store.last_change_tick, store.change_tick())
+    }
+
+    /// # Safety
+    /// Same as `new_unchecked`.
+    pub unsafe fn with_ticks(
+        store: &'w ComponentStorage<'w>,
+        last_run: Tick,
+        this_run: Tick,
+    ) -> Self {
+        Self {
+            store,
+            last_run,
+            this_run,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Panics if `Q` borrows the same component mutably twice, e.g. `(&mut Size, &Size)`
+    pub fn access() -> Access {
+        let mut access = Access::new();
+        Q::update_access(&mut access);
+        F::update_access(&mut access);
+        access
+    }
+
+    pub fn iter(&self) -> QueryIter<'_, Q::ReadOnly, F> {
+        QueryIter::new(self.store, self.last_run, self.this_run)
+    }
+
+    pub fn iter_mut(&mut self) -> QueryIter<'_, Q, F> {
+        QueryIter::new(self.store, self.last_run, self.this_run)
+    }
+
+    pub fn get(&self, entity: Entity) -> Option<<Q::ReadOnly as QueryData>::Item<'_>> {
+        let fetch = Q::ReadOnly::init_fetch(self.store, self.last_run, self.this_run);
+        if Q::ReadOnly::matches(&fetch, entity) && self.filter(entity) {
+            // SAFETY: read only data can't alias
+            Some(unsafe { Q::ReadOnly::fetch(&fetch, entity) })
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, entity: Entity) -> Option<Q::Item<'_>> {
+        let fetch = Q::init_fetch(self.store, self.last_run, self.this_run);
+        if Q::matches(&fetch, entity) && self.filter(entity) {
+            // SAFETY: the item borrows `self` mutably so no other item can exist alongside it
+            Some(unsafe { Q::fetch(&fetch, entity) })
+        } else {
+            None
+        }
+    }
+
+    /// Mutable items for several entities at once, in the order given. Fails if any entity
+    /// doesn't match or the same one is asked for twice
+    pub fn get_many_mut<const N: usize>(
+        &mut self,
+        entities: [Entity; N],
+    ) -> Result<[Q::Item<'_>; N], QueryError> {
+        for (i, entity) in entities.iter().enumerate() {
+            if entities[..i].contains(entity) {
+                return Err(QueryError::AliasedEntity(*entity));
+            }
+            if !self.contains(*entity) {
+                return Err(QueryError::NoMatch(*entity));
+            }
+        }
+        let fetch = Q::init_fetch(self.store, self.last_run, self.this_run);
+        // SAFETY: every entity matched and they're all different, so no two items alias, and
+        // they all borrow `self` mutably
+        Ok(entities.map(|entity| unsafe { Q::fetch(&fetch, entity) }))
+    }
+
+    /// The only entity that matches, for queries like the player's that should always have
+    /// exactly one
+    pub fn single(&self) -> Result<(Entity, <Q::ReadOnly as QueryData>::Item<'_>), QueryError> {
+        let mut iter = self.iter();
+        match (iter.next(), iter.next()) {
+            (Some(single), None) => Ok(single),
+            (None, _) => Err(QueryError::NoEntities(type_name::<Q>())),
+            (Some(_), Some(_)) => Err(QueryError::MultipleEntities(type_name::<Q>())),
+        }
+    }
+
+    pub fn single_mut(&mut self) -> Result<(Entity, Q::Item<'_>), QueryError> {
+        let mut iter = self.iter_mut();
+        let single = iter.next();
+        // Only the entity of the second match is looked at, its item is never made
+        let another = iter
+            .entities
+            .any(|entity| Q::matches(&iter.fetch, entity) && F::filter(&iter.filter, entity));
+        match (single, another) {
+            (Some(single), false) => Ok(single),
+            (None, _) => Err(QueryError::NoEntities(type_name::<Q>())),
+            (Some(_), true) => Err(QueryError::MultipleEntities(type_name::<Q>())),
+        }
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        let fetch = Q::init_fetch(self.store, self.last_run, self.this_run);
+        Q::matches(&fetch, entity) && self.filter(entity)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    fn filter(&self, entity: Entity) -> bool {
+        let fetch = F::init_fetch(self.store, self.last_run, self.this_run);
+        F::filter(&fetch, entity)
+    }
+}
+
+impl<'q, 'w, Q: QueryData, F: QueryFilter> IntoIterator for &'q Query<'w, Q, F> {
+    type Item = (Entity, <Q::ReadOnly as QueryData>::Item<'q>);
+    type IntoIter = QueryIter<'q, Q::ReadOnly, F>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'q, 'w, Q: QueryData, F: QueryFilter> IntoIterator for &'q mut Query<'w, Q, F> {
+    type Item = (Entity, Q::Item<'q>);
+    type IntoIter = QueryIter<'q, Q, F>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum QueryError {
+    /// `single` found nothing matching the query
+    NoEntities(&'static str),
+    /// `single` found more than one entity matching the query
+    MultipleEntities(&'static str),
+    /// The entity is dead or doesn't match the query
+    NoMatch(Entity),
+    /// The same entity was asked for twice in `get_many_mut`
+    AliasedEntity(Entity),
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryError::NoEntities(query) => write!(f, "no entity matches {}", query),
+            QueryError::MultipleEntities(query) => {
+                write!(f, "more than one entity matches {}", query)
+            }
+            QueryError::NoMatch(entity) => write!(f, "entity {:?} does not match", entity),
+            QueryError::AliasedEntity(entity) => {
+                write!(f, "entity {:?} was asked for more than once", entity)
+            }
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+pub struct QueryIter<'w, Q: QueryData, F: QueryFilter> {
+    fetch: Q::Fetch<'w>,
+    filter: F::Fetch<'w>,
+    entities: std::vec::IntoIter<Entity>,
+}
+
+impl<'w, Q: QueryData, F: QueryFilter> QueryIter<'w, Q, F> {
+    fn new(store: &'w ComponentStorage<'w>, last_run: Tick, this_run: Tick) -> Self {
+        let fetch = Q::init_fetch(store, last_run, this_run);
+        let filter = F::init_fetch(store, last_run, this_run);
+        let entities = match smallest_set(Q::candidates(&fetch), F::candidates(&filter)) {
+            Some(set) => set.entities(),
+            None => store.allocator.keys().collect(),
+        };
+        Self {
+            fetch,
+            filter,
+            entities: entities.into_iter(),
+        }
+    }
+}
+
+impl<'w, Q: QueryData, F: QueryFilter> Iterator for QueryIter<'w, Q, F> {
+    type Item = (Entity, Q::Item<'w>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for entity in self.entities.by_ref() {
+            if Q::matches(&self.fetch, entity) && F::filter(&self.filter, entity) {
+                // SAFETY: every entity is visited once and the iterator borrows the query for
+                // 'w, so items for the same entity can never be alive twice
+                return Some((entity, unsafe { Q::fetch(&self.fetch, entity) }));
+            }
+        }
+        None
+    }
+}
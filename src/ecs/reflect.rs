@@ -0,0 +1,304 @@
+use std::{
+    any::{type_name, Any, TypeId},
+    collections::HashMap,
+    fmt, mem,
+};
+
+use nalgebra::{Matrix4, Vector3};
+
+pub use reflect_derive::Reflect;
+
+use super::{scene::short_name, Component, ComponentStorage, Entity};
+
+/// Runtime access to a type's fields by name. Derive it for structs, fields are reached by
+/// name or by index for tuple structs and every field type has to be `Reflect` too. Plain
+/// values like numbers are leaves with no fields
+pub trait Reflect: Any + Send + Sync {
+    fn type_info() -> TypeInfo
+    where
+        Self: Sized;
+
+    fn as_any(&self) -> &dyn Any;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    fn field(&self, name: &str) -> Option<&dyn Reflect>;
+
+    fn field_mut(&mut self, name: &str) -> Option<&mut dyn Reflect>;
+}
+
+impl dyn Reflect {
+    /// Follows a `.` separated path of field names, e.g. `"transform.x"`
+    pub fn path(&self, path: &str) -> Option<&dyn Reflect> {
+        path.split('.')
+            .try_fold(self, |value, field| value.field(field))
+    }
+
+    pub fn path_mut(&mut self, path: &str) -> Option<&mut dyn Reflect> {
+        path.split('.')
+            .try_fold(self, |value, field| value.field_mut(field))
+    }
+
+    pub fn get_path<T: 'static>(&self, path: &str) -> Result<&T, ReflectError> {
+        self.path(path)
+            .ok_or_else(|| ReflectError::NoField(path.to_string()))?
+            .as_any()
+            .downcast_ref()
+            .ok_or_else(|| ReflectError::WrongType {
+                path: path.to_string(),
+                expected: type_name::<T>(),
+            })
+    }
+
+    pub fn set_path<T: 'static>(&mut self, path: &str, value: T) -> Result<(), ReflectError> {
+        let field = self
+            .path_mut(path)
+            .ok_or_else(|| ReflectError::NoField(path.to_string()))?
+            .as_any_mut()
+            .downcast_mut()
+            .ok_or_else(|| ReflectError::WrongType {
+                path: path.to_string(),
+                expected: type_name::<T>(),
+            })?;
+        *field = value;
+        Ok(())
+    }
+}
+
+// Leaves, reflected as a whole with no fields of their own
+macro_rules! impl_reflect_value {
+    ($($ty:ty),*) => {
+        $(
+            impl Reflect for $ty {
+                fn type_info() -> TypeInfo {
+                    TypeInfo::new::<Self>(Vec::new())
+                }
+
+                fn as_any(&self) -> &dyn Any {
+                    self
+                }
+
+                fn as_any_mut(&mut self) -> &mut dyn Any {
+                    self
+                }
+
+                fn field(&self, _name: &str) -> Option<&dyn Reflect> {
+                    None
+                }
+
+                fn field_mut(&mut self, _name: &str) -> Option<&mut dyn Reflect> {
+                    None
+                }
+            }
+        )*
+    };
+}
+
+impl_reflect_value!(
+    bool,
+    u8,
+    u16,
+    u32,
+    u64,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    isize,
+    f32,
+    f64,
+    String,
+    Entity,
+    Vec<Entity>,
+    Matrix4<f32>
+);
+
+// Spelled out so paths like `transform.x` reach into vectors
+impl Reflect for Vector3<f32> {
+    fn type_info() -> TypeInfo {
+        TypeInfo::new::<Self>(vec![
+            FieldInfo::new::<f32>("x"),
+            FieldInfo::new::<f32>("y"),
+            FieldInfo::new::<f32>("z"),
+        ])
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn field(&self, name: &str) -> Option<&dyn Reflect> {
+        match name {
+            "x" => Some(&self.x),
+            "y" => Some(&self.y),
+            "z" => Some(&self.z),
+            _ => None,
+        }
+    }
+
+    fn field_mut(&mut self, name: &str) -> Option<&mut dyn Reflect> {
+        match name {
+            "x" => Some(&mut self.x),
+            "y" => Some(&mut self.y),
+            "z" => Some(&mut self.z),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldInfo {
+    pub name: &'static str,
+    pub type_name: &'static str,
+    pub type_id: TypeId,
+}
+
+impl FieldInfo {
+    pub fn new<T: 'static>(name: &'static str) -> Self {
+        Self {
+            name,
+            type_name: type_name::<T>(),
+            type_id: TypeId::of::<T>(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypeInfo {
+    pub type_id: TypeId,
+    /// The full path, e.g. `oxygin::components::Position`
+    pub type_name: &'static str,
+    /// Without the outer type's path, e.g. `Position` or `Events<oxygin::events::Eaten>`
+    pub name: &'static str,
+    pub size: usize,
+    pub fields: Vec<FieldInfo>,
+}
+
+impl TypeInfo {
+    pub fn new<T: 'static>(fields: Vec<FieldInfo>) -> Self {
+        Self {
+            type_id: TypeId::of::<T>(),
+            type_name: type_name::<T>(),
+            name: short_name::<T>(),
+            size: mem::size_of::<T>(),
+            fields,
+        }
+    }
+
+    pub fn field(&self, name: &str) -> Option<&FieldInfo> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+}
+
+type ReflectComponent = for<'s, 'w> fn(&'s ComponentStorage<'w>, Entity) -> Option<&'s dyn Reflect>;
+type ReflectComponentMut =
+    for<'s, 'w> fn(&'s mut ComponentStorage<'w>, Entity) -> Option<&'s mut dyn Reflect>;
+
+/// A registered component's layout plus how to find its value on an entity without
+/// knowing the type
+pub struct TypeRegistration {
+    pub info: TypeInfo,
+    reflect: ReflectComponent,
+    reflect_mut: ReflectComponentMut,
+}
+
+impl TypeRegistration {
+    pub fn of<T: Component + 'static>() -> Self {
+        Self {
+            info: T::type_info(),
+            reflect: |store, entity| {
+                let component = store.get::<T>().ok()?.get(entity)?;
+                Some(component as &dyn Reflect)
+            },
+            reflect_mut: |store, entity| {
+                let tick = store.change_tick();
+                let component = store.get_mut::<T>().ok()?.get_mut(entity, tick)?;
+                Some(component.into_inner() as &mut dyn Reflect)
+            },
+        }
+    }
+}
+
+impl<'a> ComponentStorage<'a> {
+    /// Finds a component on the entity by its type name, for tools that only have a string
+    pub fn reflect(&self, entity: Entity, name: &str) -> Option<&dyn Reflect> {
+        (self.types.get_by_name(name)?.reflect)(self, entity)
+    }
+
+    /// Marks the component changed up front, since there's no telling what the caller writes
+    pub fn reflect_mut(&mut self, entity: Entity, name: &str) -> Option<&mut dyn Reflect> {
+        let reflect_mut = self.types.get_by_name(name)?.reflect_mut;
+        reflect_mut(self, entity)
+    }
+}
+
+/// Every registered component by `TypeId` and by name, filled in by
+/// `ComponentStorage::register`
+#[derive(Default)]
+pub struct TypeRegistry {
+    types: HashMap<TypeId, TypeRegistration>,
+    names: HashMap<&'static str, TypeId>,
+}
+
+impl TypeRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register<T: Component + 'static>(&mut self) {
+        let registration = TypeRegistration::of::<T>();
+        self.names
+            .insert(registration.info.name, registration.info.type_id);
+        self.names
+            .insert(registration.info.type_name, registration.info.type_id);
+        self.types.insert(registration.info.type_id, registration);
+    }
+
+    pub fn get(&self, type_id: TypeId) -> Option<&TypeRegistration> {
+        self.types.get(&type_id)
+    }
+
+    /// Takes either the full type name or just the last segment
+    pub fn get_by_name(&self, name: &str) -> Option<&TypeRegistration> {
+        self.get(*self.names.get(name)?)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &TypeRegistration> + '_ {
+        self.types.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.types.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReflectError {
+    NoField(String),
+    WrongType {
+        path: String,
+        expected: &'static str,
+    },
+}
+
+impl fmt::Display for ReflectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReflectError::NoField(path) => write!(f, "no field at {}", path),
+            ReflectError::WrongType { path, expected } => {
+                write!(f, "field at {} is not a {}", path, expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReflectError {}
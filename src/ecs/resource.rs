@@ -1,10 +1,28 @@
 use std::{
-    any::{Any, TypeId},
+    any::{type_name, Any, TypeId},
+    cell::UnsafeCell,
     collections::HashMap,
+    ops::{Deref, DerefMut},
 };
 
+use super::{AccessId, SystemAccess, SystemParam, Tick, ECS};
+
+// Resources live behind an UnsafeCell so `ResMut` can be handed to a system while other
+// parameters borrow the rest of the world
+pub struct ResourceCell(UnsafeCell<Box<dyn Any + Send + Sync>>);
+
+// SAFETY: only handed out mutably through `get_mut` or `get_unchecked_mut`, the scheduler
+// keeps systems that write the same resource from overlapping
+unsafe impl Sync for ResourceCell {}
+
 pub struct ResourceStorage {
-    pub storage: HashMap<TypeId, Box<dyn Any>>,
+    pub storage: HashMap<TypeId, ResourceCell>,
+}
+
+impl Default for ResourceStorage {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ResourceStorage {
@@ -14,17 +32,134 @@ impl ResourceStorage {
         }
     }
 
-    pub fn insert<T: 'static>(&mut self, resource: T) {
-        self.storage.insert(TypeId::of::<T>(), Box::new(resource));
+    pub fn insert<T: Send + Sync + 'static>(&mut self, resource: T) {
+        self.storage.insert(
+            TypeId::of::<T>(),
+            ResourceCell(UnsafeCell::new(Box::new(resource))),
+        );
     }
 
     pub fn get<T: 'static>(&self) -> Option<&T> {
         let resource_ref = self.storage.get(&TypeId::of::<T>())?;
-        resource_ref.downcast_ref::<T>()
+        unsafe { &*resource_ref.0.get() }.downcast_ref::<T>()
     }
 
     pub fn get_mut<T: 'static>(&mut self) -> Option<&mut T> {
         let resource_ref = self.storage.get_mut(&TypeId::of::<T>())?;
-        resource_ref.downcast_mut::<T>()
+        resource_ref.0.get_mut().downcast_mut::<T>()
+    }
+
+    pub fn remove<T: 'static>(&mut self) -> Option<T> {
+        let resource_ref = self.storage.remove(&TypeId::of::<T>())?;
+        resource_ref
+            .0
+            .into_inner()
+            .downcast::<T>()
+            .ok()
+            .map(|resource| *resource)
+    }
+
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.storage.contains_key(&TypeId::of::<T>())
+    }
+
+    /// # Safety
+    /// Nothing else may reference the resource for the returned lifetime.
+    #[allow(clippy::mut_from_ref)]
+    pub unsafe fn get_unchecked_mut<T: 'static>(&self) -> Option<&mut T> {
+        let resource_ref = self.storage.get(&TypeId::of::<T>())?;
+        (*resource_ref.0.get()).downcast_mut::<T>()
+    }
+}
+
+/// Builds a resource out of whatever is already in the world, anything `Default` gets it
+/// for free
+pub trait FromWorld {
+    fn from_world(ecs: &mut ECS) -> Self;
+}
+
+impl<T: Default> FromWorld for T {
+    fn from_world(_ecs: &mut ECS) -> Self {
+        T::default()
+    }
+}
+
+/// Shared access to a resource from a system
+pub struct Res<'w, T> {
+    value: &'w T,
+}
+
+impl<'w, T> Deref for Res<'w, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+/// Exclusive access to a resource from a system
+pub struct ResMut<'w, T> {
+    value: &'w mut T,
+}
+
+impl<'w, T> Deref for ResMut<'w, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<'w, T> DerefMut for ResMut<'w, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value
+    }
+}
+
+unsafe impl<'a, T: 'static> SystemParam for Res<'a, T> {
+    type State = ();
+    type Item<'w> = Res<'w, T>;
+
+    fn init_state(_ecs: &mut ECS) {}
+
+    fn update_access(access: &mut SystemAccess) {
+        access.resources.add_read(AccessId::of::<T>());
+    }
+
+    unsafe fn get_param<'w>(
+        _state: &'w mut (),
+        ecs: &'w ECS<'w>,
+        _last_run: Tick,
+        _this_run: Tick,
+    ) -> Res<'w, T> {
+        let value = ecs
+            .resources
+            .get::<T>()
+            .unwrap_or_else(|| panic!("resource {} does not exist", type_name::<T>()));
+        Res { value }
+    }
+}
+
+unsafe impl<'a, T: 'static> SystemParam for ResMut<'a, T> {
+    type State = ();
+    type Item<'w> = ResMut<'w, T>;
+
+    fn init_state(_ecs: &mut ECS) {}
+
+    fn update_access(access: &mut SystemAccess) {
+        access.resources.add_write(AccessId::of::<T>());
+    }
+
+    unsafe fn get_param<'w>(
+        _state: &'w mut (),
+        ecs: &'w ECS<'w>,
+        _last_run: Tick,
+        _this_run: Tick,
+    ) -> ResMut<'w, T> {
+        let value = ecs
+            .resources
+            .get_unchecked_mut::<T>()
+            .unwrap_or_else(|| panic!("resource {} does not exist", type_name::<T>()));
+        ResMut { value }
     }
 }
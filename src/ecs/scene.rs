@@ -0,0 +1,209 @@
+use std::{any::type_name, collections::BTreeMap, collections::HashMap, fmt};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::{Component, ComponentStorage, EcsError, Entity, Parent, ECS};
+
+// Components go through a json value on the way in and out so the registry doesn't have to
+// care which format the scene is written in
+type SaveComponent = fn(&ComponentStorage, Entity) -> Result<Option<serde_json::Value>, SceneError>;
+type LoadComponent = fn(&mut ComponentStorage, Entity, serde_json::Value) -> Result<(), SceneError>;
+type RequireComponents = fn(&mut ComponentStorage, Entity) -> Result<(), EcsError>;
+
+struct SceneComponent {
+    name: &'static str,
+    save: SaveComponent,
+    load: LoadComponent,
+    require: RequireComponents,
+}
+
+/// The components that get written to and read from scenes, anything not in here is skipped
+/// when saving
+#[derive(Default)]
+pub struct SceneRegistry {
+    components: Vec<SceneComponent>,
+}
+
+impl SceneRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Components are keyed by their type name without the module path, registering the
+    /// same name twice replaces the first
+    pub fn register<T>(&mut self)
+    where
+        T: Component + Serialize + DeserializeOwned + 'static,
+    {
+        let name = short_name::<T>();
+        self.components.retain(|component| component.name != name);
+        self.components.push(SceneComponent {
+            name,
+            save: |store, entity| {
+                store
+                    .get::<T>()?
+                    .get(entity)
+                    .map(serde_json::to_value)
+                    .transpose()
+                    .map_err(SceneError::Json)
+            },
+            load: |store, entity, value| {
+                let component: T = serde_json::from_value(value).map_err(SceneError::Json)?;
+                store.insert_without_required(entity, component)?;
+                Ok(())
+            },
+            require: |store, entity| store.insert_required::<T>(entity),
+        });
+    }
+
+    fn get(&self, name: &str) -> Option<&SceneComponent> {
+        self.components
+            .iter()
+            .find(|component| component.name == name)
+    }
+}
+
+// Only the outer type loses its path, `a::Foo<b::Bar>` is saved as `Foo<b::Bar>`
+pub(crate) fn short_name<T>() -> &'static str {
+    let name = type_name::<T>();
+    let outer = name.find('<').map_or(name, |generics| &name[..generics]);
+    match outer.rfind("::") {
+        Some(path) => &name[path + 2..],
+        None => name,
+    }
+}
+
+/// A saved set of entities. Entities are numbered from 0 in the order they were saved, the
+/// numbers only mean anything inside the scene and are swapped for fresh keys on load
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Scene {
+    pub entities: Vec<SceneEntity>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SceneEntity {
+    pub id: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<u32>,
+    pub components: BTreeMap<String, serde_json::Value>,
+}
+
+impl Scene {
+    /// Captures every live entity and whichever of its components are in the registry
+    pub fn from_ecs(ecs: &ECS) -> Result<Self, SceneError> {
+        let store = &ecs.store;
+        let ids: HashMap<Entity, u32> = store
+            .allocator
+            .keys()
+            .enumerate()
+            .map(|(id, entity)| (entity, id as u32))
+            .collect();
+        let parents = store.get::<Parent>()?;
+
+        let mut entities = Vec::with_capacity(ids.len());
+        for (id, entity) in store.allocator.keys().enumerate() {
+            let mut components = BTreeMap::new();
+            for component in &ecs.scene_registry.components {
+                if let Some(value) = (component.save)(store, entity)? {
+                    components.insert(component.name.to_string(), value);
+                }
+            }
+            entities.push(SceneEntity {
+                id: id as u32,
+                parent: parents.get(entity).map(|parent| ids[&parent.get()]),
+                components,
+            });
+        }
+        Ok(Self { entities })
+    }
+
+    /// Spawns a fresh copy of every entity in the scene, handing back which key each scene
+    /// id ended up as. An error stops it part way, whatever was spawned by then stays
+    pub fn spawn(&self, ecs: &mut ECS) -> Result<HashMap<u32, Entity>, SceneError> {
+        let mut entities = HashMap::with_capacity(self.entities.len());
+        for scene_entity in &self.entities {
+            let entity = ecs.create_entity();
+            if entities.insert(scene_entity.id, entity).is_some() {
+                return Err(SceneError::DuplicateId(scene_entity.id));
+            }
+            let mut loaded = Vec::with_capacity(scene_entity.components.len());
+            for (name, value) in &scene_entity.components {
+                let component = ecs
+                    .scene_registry
+                    .get(name)
+                    .ok_or_else(|| SceneError::UnknownComponent(name.clone()))?;
+                (component.load)(&mut ecs.store, entity, value.clone())?;
+                loaded.push(component.require);
+            }
+            // Like a bundle, saved components go in first and requirements only fill the gaps
+            for require in loaded {
+                require(&mut ecs.store, entity)?;
+            }
+        }
+        ecs.flush_hooks();
+        // Parents can come after their children in the file, so these wait until everything
+        // has a key
+        for scene_entity in &self.entities {
+            if let Some(parent) = scene_entity.parent {
+                let parent = *entities
+                    .get(&parent)
+                    .ok_or(SceneError::UnknownEntity(parent))?;
+                ecs.set_parent(entities[&scene_entity.id], parent)?;
+            }
+        }
+        Ok(entities)
+    }
+
+    pub fn to_ron(&self) -> Result<String, SceneError> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).map_err(SceneError::Ron)
+    }
+
+    pub fn from_ron(ron: &str) -> Result<Self, SceneError> {
+        ron::from_str(ron).map_err(SceneError::RonParse)
+    }
+
+    pub fn to_json(&self) -> Result<String, SceneError> {
+        serde_json::to_string_pretty(self).map_err(SceneError::Json)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, SceneError> {
+        serde_json::from_str(json).map_err(SceneError::Json)
+    }
+}
+
+#[derive(Debug)]
+pub enum SceneError {
+    Ecs(EcsError),
+    Ron(ron::Error),
+    RonParse(ron::error::SpannedError),
+    Json(serde_json::Error),
+    /// The scene has a component nobody registered with `ECS::register_scene_component`
+    UnknownComponent(String),
+    /// A parent id that no entity in the scene has
+    UnknownEntity(u32),
+    DuplicateId(u32),
+}
+
+impl From<EcsError> for SceneError {
+    fn from(error: EcsError) -> Self {
+        SceneError::Ecs(error)
+    }
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Ecs(error) => write!(f, "{}", error),
+            SceneError::Ron(error) => write!(f, "couldn't write ron scene: {}", error),
+            SceneError::RonParse(error) => write!(f, "invalid ron scene: {}", error),
+            SceneError::Json(error) => write!(f, "json scene: {}", error),
+            SceneError::UnknownComponent(name) => {
+                write!(f, "component {} is not registered for scenes", name)
+            }
+            SceneError::UnknownEntity(id) => write!(f, "scene has no entity {}", id),
+            SceneError::DuplicateId(id) => write!(f, "scene has entity {} more than once", id),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
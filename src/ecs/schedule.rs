@@ -0,0 +1,361 @@
+use std::{
+    any::{type_name, TypeId},
+    collections::HashMap,
+    fmt,
+};
+
+use super::{BoxedSystem, IntoSystem, ECS};
+
+/// Commands queued by a stage's systems are applied once the whole stage has finished
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Stage {
+    /// Runs once before the first update
+    Startup,
+    /// Runs every frame before the fixed update, e.g. to turn window input into components
+    PreUpdate,
+    /// Runs at the scheduler's fixed tick rate, zero or more times a frame
+    FixedUpdate,
+    Update,
+    PostUpdate,
+    /// Runs right before the frame is drawn
+    Render,
+}
+
+/// Identifies a system for ordering, every function has its own type so that's used
+#[derive(Clone, Copy, Debug)]
+pub struct SystemLabel {
+    pub id: TypeId,
+    pub name: &'static str,
+}
+
+impl SystemLabel {
+    pub fn of<T: 'static>() -> Self {
+        Self {
+            id: TypeId::of::<T>(),
+            name: type_name::<T>(),
+        }
+    }
+}
+
+impl PartialEq for SystemLabel {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for SystemLabel {}
+
+pub struct SystemConfig {
+    pub system: BoxedSystem,
+    pub label: SystemLabel,
+    pub before: Vec<SystemLabel>,
+    pub after: Vec<SystemLabel>,
+}
+
+pub struct IsSystemConfig;
+
+/// Lets ordering constraints be chained onto a system before it is added,
+/// e.g. `app.add_system(Stage::Update, move_system.after(handle_input_system))`
+pub trait IntoSystemConfig<Marker>: Sized {
+    fn into_config(self) -> SystemConfig;
+
+    fn before<M, S>(self, _system: S) -> SystemConfig
+    where
+        S: IntoSystem<M> + 'static,
+    {
+        let mut config = self.into_config();
+        config.before.push(SystemLabel::of::<S>());
+        config
+    }
+
+    fn after<M, S>(self, _system: S) -> SystemConfig
+    where
+        S: IntoSystem<M> + 'static,
+    {
+        let mut config = self.into_config();
+        config.after.push(SystemLabel::of::<S>());
+        config
+    }
+}
+
+impl<M, S> IntoSystemConfig<M> for S
+where
+    S: IntoSystem<M> + 'static,
+{
+    fn into_config(self) -> SystemConfig {
+        SystemConfig {
+            system: Box::new(self.into_system()),
+            label: SystemLabel::of::<S>(),
+            before: Vec::new(),
+            after: Vec::new(),
+        }
+    }
+}
+
+impl IntoSystemConfig<IsSystemConfig> for SystemConfig {
+    fn into_config(self) -> SystemConfig {
+        self
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ScheduleError {
+    /// The ordering constraints in a stage loop back on themselves
+    Cycle {
+        stage: Stage,
+        systems: Vec<&'static str>,
+    },
+    /// An ordering constraint names a system that isn't in the same stage
+    UnknownSystem {
+        stage: Stage,
+        system: &'static str,
+        dependency: &'static str,
+    },
+}
+
+impl fmt::Display for ScheduleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScheduleError::Cycle { stage, systems } => write!(
+                f,
+                "systems in {:?} have cyclic ordering: {}",
+                stage,
+                systems.join(", ")
+            ),
+            ScheduleError::UnknownSystem {
+                stage,
+                system,
+                dependency,
+            } => write!(
+                f,
+                "{} is ordered against {} which is not in {:?}",
+                system, dependency, stage
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ScheduleError {}
+
+#[derive(Default)]
+pub struct SystemStage {
+    pub systems: Vec<SystemConfig>,
+    // Groups of systems that can run at the same time, in the order they have to run
+    batches: Option<Vec<Vec<usize>>>,
+}
+
+impl SystemStage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_system(&mut self, config: SystemConfig) {
+        self.systems.push(config);
+        self.batches = None;
+    }
+
+    /// Sorts the systems so every `before`/`after` constraint holds, otherwise keeping the
+    /// order they were added in, then groups them into batches that can run in parallel
+    pub fn build(&mut self, stage: Stage) -> Result<(), ScheduleError> {
+        if self.batches.is_some() {
+            return Ok(());
+        }
+
+        let count = self.systems.len();
+        let mut edges: Vec<Vec<usize>> = vec![Vec::new(); count];
+        let mut dependencies: Vec<Vec<usize>> = vec![Vec::new(); count];
+        let mut in_degree = vec![0; count];
+
+        for (index, config) in self.systems.iter().enumerate() {
+            for label in &config.before {
+                for target in self.find(stage, config, label)? {
+                    edges[index].push(target);
+                    dependencies[target].push(index);
+                    in_degree[target] += 1;
+                }
+            }
+            for label in &config.after {
+                for source in self.find(stage, config, label)? {
+                    edges[source].push(index);
+                    dependencies[index].push(source);
+                    in_degree[index] += 1;
+                }
+            }
+        }
+
+        let mut order = Vec::with_capacity(count);
+        let mut ready: Vec<usize> = (0..count).filter(|i| in_degree[*i] == 0).collect();
+        while let Some(next) = ready.iter().copied().min() {
+            ready.retain(|i| *i != next);
+            order.push(next);
+            for target in &edges[next] {
+                in_degree[*target] -= 1;
+                if in_degree[*target] == 0 {
+                    ready.push(*target);
+                }
+            }
+        }
+
+        if order.len() < count {
+            return Err(ScheduleError::Cycle {
+                stage,
+                systems: find_cycle(&dependencies, &in_degree)
+                    .into_iter()
+                    .map(|i| self.systems[i].label.name)
+                    .collect(),
+            });
+        }
+
+        self.batches = Some(self.batch(order, &dependencies));
+        Ok(())
+    }
+
+    // Walks the sorted systems pulling each one into the current batch if everything it
+    // depends on has already run and it doesn't conflict with anything ahead of it that
+    // hasn't, so systems sharing data still run in their sorted order
+    fn batch(&self, order: Vec<usize>, dependencies: &[Vec<usize>]) -> Vec<Vec<usize>> {
+        let mut done = vec![false; self.systems.len()];
+        let mut pending = order;
+        let mut batches = Vec::new();
+
+        while !pending.is_empty() {
+            let mut batch: Vec<usize> = Vec::new();
+            let mut waiting: Vec<usize> = Vec::new();
+
+            for index in pending {
+                let access = self.systems[index].system.access();
+                let ready = dependencies[index]
+                    .iter()
+                    .all(|dependency| done[*dependency])
+                    && batch
+                        .iter()
+                        .chain(&waiting)
+                        .all(|other| access.is_compatible(self.systems[*other].system.access()));
+                if ready {
+                    batch.push(index);
+                } else {
+                    waiting.push(index);
+                }
+            }
+
+            batch.iter().for_each(|index| done[*index] = true);
+            batches.push(batch);
+            pending = waiting;
+        }
+
+        batches
+    }
+
+    fn find(
+        &self,
+        stage: Stage,
+        config: &SystemConfig,
+        label: &SystemLabel,
+    ) -> Result<Vec<usize>, ScheduleError> {
+        let found: Vec<usize> = self
+            .systems
+            .iter()
+            .enumerate()
+            .filter(|(_, other)| other.label == *label)
+            .map(|(index, _)| index)
+            .collect();
+        if found.is_empty() {
+            return Err(ScheduleError::UnknownSystem {
+                stage,
+                system: config.label.name,
+                dependency: label.name,
+            });
+        }
+        Ok(found)
+    }
+
+    pub fn run(&mut self, stage: Stage, ecs: &mut ECS) -> Result<(), ScheduleError> {
+        self.build(stage)?;
+        let Some(batches) = &self.batches else {
+            return Ok(());
+        };
+
+        for batch in batches {
+            if let [index] = batch[..] {
+                self.systems[index].system.run(ecs);
+                continue;
+            }
+
+            let ecs: &ECS = ecs;
+            let mut systems: Vec<&mut SystemConfig> = self
+                .systems
+                .iter_mut()
+                .enumerate()
+                .filter(|(index, _)| batch.contains(index))
+                .map(|(_, config)| config)
+                .collect();
+            rayon::scope(|scope| {
+                for config in systems.iter_mut() {
+                    // SAFETY: `batch` only groups systems whose access doesn't conflict and
+                    // never groups an exclusive or main thread system with anything
+                    scope.spawn(move |_| unsafe { config.system.run_unsafe(ecs) });
+                }
+            });
+        }
+
+        // Sync point, nothing is iterating so structural changes are safe
+        ecs.apply_commands();
+        Ok(())
+    }
+}
+
+// Every system the sort never reached still waits on another one it never reached, so
+// walking back through those dependencies has to come round to a system already seen. Only
+// the loop itself is returned, not the systems stuck behind it
+fn find_cycle(dependencies: &[Vec<usize>], in_degree: &[usize]) -> Vec<usize> {
+    let mut path: Vec<usize> = Vec::new();
+    let mut next = in_degree.iter().position(|degree| *degree > 0);
+    while let Some(index) = next {
+        if let Some(start) = path.iter().position(|seen| *seen == index) {
+            let mut cycle = path.split_off(start);
+            // Walked backwards, turn it round and start from the earliest added system
+            cycle.reverse();
+            let first = (0..cycle.len()).min_by_key(|i| cycle[*i]).unwrap_or(0);
+            cycle.rotate_left(first);
+            return cycle;
+        }
+        path.push(index);
+        next = dependencies[index]
+            .iter()
+            .copied()
+            .find(|dependency| in_degree[*dependency] > 0);
+    }
+    path
+}
+
+#[derive(Default)]
+pub struct Schedule {
+    pub stages: HashMap<Stage, SystemStage>,
+}
+
+impl Schedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_system<M>(&mut self, stage: Stage, ecs: &mut ECS, system: impl IntoSystemConfig<M>) {
+        let mut config = system.into_config();
+        config.system.initialize(ecs);
+        self.stages.entry(stage).or_default().add_system(config);
+    }
+
+    /// Checks the ordering of every stage up front so mistakes show up before the first frame
+    pub fn build(&mut self) -> Result<(), ScheduleError> {
+        for (stage, systems) in self.stages.iter_mut() {
+            systems.build(*stage)?;
+        }
+        Ok(())
+    }
+
+    pub fn run_stage(&mut self, stage: Stage, ecs: &mut ECS) -> Result<(), ScheduleError> {
+        match self.stages.get_mut(&stage) {
+            Some(systems) => systems.run(stage, ecs),
+            None => Ok(()),
+        }
+    }
+}
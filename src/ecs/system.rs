@@ -1,3 +1,282 @@
-trait System {
-    fn update();
+use std::{any::type_name, marker::PhantomData};
+
+use super::{Query, QueryData, QueryFilter, SystemAccess, Tick, ECS};
+
+pub trait System: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Works out what the system accesses, panicking if its own parameters conflict
+    fn initialize(&mut self, ecs: &mut ECS);
+
+    fn access(&self) -> &SystemAccess;
+
+    /// Runs through a shared borrow so several systems can run at once
+    ///
+    /// # Safety
+    /// Nothing running at the same time may conflict with `access`, exclusive systems can't
+    /// be run this way at all.
+    unsafe fn run_unsafe(&mut self, ecs: &ECS);
+
+    fn run(&mut self, ecs: &mut ECS);
+}
+
+pub type BoxedSystem = Box<dyn System>;
+
+/// Anything a system function can take as an argument, fetched from the `ECS` every run.
+///
+/// # Safety
+/// `update_access` must report everything `get_param` borrows, with mutable borrows reported
+/// as writes.
+pub unsafe trait SystemParam {
+    /// Kept by the system between runs, e.g. how far an `EventReader` has read
+    type State: Send + Sync + 'static;
+    type Item<'w>;
+
+    fn init_state(ecs: &mut ECS) -> Self::State;
+
+    fn update_access(access: &mut SystemAccess);
+
+    /// # Safety
+    /// The access reported by `update_access` must not overlap with anything else alive.
+    unsafe fn get_param<'w>(
+        state: &'w mut Self::State,
+        ecs: &'w ECS<'w>,
+        last_run: Tick,
+        this_run: Tick,
+    ) -> Self::Item<'w>;
+}
+
+unsafe impl<'a, Q, F> SystemParam for Query<'a, Q, F>
+where
+    Q: QueryData + 'static,
+    F: QueryFilter + 'static,
+{
+    type State = ();
+    type Item<'w> = Query<'w, Q, F>;
+
+    fn init_state(_ecs: &mut ECS) {}
+
+    fn update_access(access: &mut SystemAccess) {
+        access.components.extend(&Query::<Q, F>::access());
+    }
+
+    unsafe fn get_param<'w>(
+        _state: &'w mut (),
+        ecs: &'w ECS<'w>,
+        last_run: Tick,
+        this_run: Tick,
+    ) -> Query<'w, Q, F> {
+        Query::with_ticks(&ecs.store, last_run, this_run)
+    }
+}
+
+macro_rules! impl_system_param_tuple {
+    ($($param:ident),*) => {
+        #[allow(non_snake_case, unused_variables, clippy::unused_unit)]
+        unsafe impl<$($param: SystemParam),*> SystemParam for ($($param,)*) {
+            type State = ($($param::State,)*);
+            type Item<'w> = ($($param::Item<'w>,)*);
+
+            fn init_state(ecs: &mut ECS) -> Self::State {
+                ($($param::init_state(ecs),)*)
+            }
+
+            fn update_access(access: &mut SystemAccess) {
+                $(
+                    let mut param = SystemAccess::new();
+                    $param::update_access(&mut param);
+                    let conflicts = access.conflicts(&param);
+                    assert!(
+                        conflicts.is_empty(),
+                        "{} conflicts with an earlier parameter over {:?}",
+                        type_name::<$param>(),
+                        conflicts.iter().map(|id| id.name).collect::<Vec<_>>()
+                    );
+                    access.extend(&param);
+                )*
+            }
+
+            unsafe fn get_param<'w>(
+                state: &'w mut Self::State,
+                ecs: &'w ECS<'w>,
+                last_run: Tick,
+                this_run: Tick,
+            ) -> Self::Item<'w> {
+                let ($($param,)*) = state;
+                ($($param::get_param($param, ecs, last_run, this_run),)*)
+            }
+        }
+    };
+}
+
+impl_system_param_tuple!();
+impl_system_param_tuple!(A);
+impl_system_param_tuple!(A, B);
+impl_system_param_tuple!(A, B, C);
+impl_system_param_tuple!(A, B, C, D);
+impl_system_param_tuple!(A, B, C, D, E);
+impl_system_param_tuple!(A, B, C, D, E, F);
+impl_system_param_tuple!(A, B, C, D, E, F, G);
+impl_system_param_tuple!(A, B, C, D, E, F, G, H);
+
+/// A plain function whose arguments are all `SystemParam`s
+pub trait SystemParamFunction<Marker>: Send + Sync + 'static {
+    type Param: SystemParam;
+
+    fn run(&mut self, param: <Self::Param as SystemParam>::Item<'_>);
+}
+
+macro_rules! impl_system_param_function {
+    ($($param:ident),*) => {
+        #[allow(non_snake_case, clippy::too_many_arguments)]
+        impl<Func, $($param: SystemParam),*> SystemParamFunction<fn($($param,)*)> for Func
+        where
+            Func: Send + Sync + 'static,
+            for<'a> &'a mut Func: FnMut($($param),*) + FnMut($($param::Item<'_>),*),
+        {
+            type Param = ($($param,)*);
+
+            fn run(&mut self, param: <Self::Param as SystemParam>::Item<'_>) {
+                // Calling through a generic function pins down which `FnMut` impl is meant
+                fn call_inner<$($param),*>(mut f: impl FnMut($($param),*), $($param: $param),*) {
+                    f($($param),*)
+                }
+                let ($($param,)*) = param;
+                call_inner(self, $($param),*)
+            }
+        }
+    };
+}
+
+impl_system_param_function!();
+impl_system_param_function!(A);
+impl_system_param_function!(A, B);
+impl_system_param_function!(A, B, C);
+impl_system_param_function!(A, B, C, D);
+impl_system_param_function!(A, B, C, D, E);
+impl_system_param_function!(A, B, C, D, E, F);
+impl_system_param_function!(A, B, C, D, E, F, G);
+impl_system_param_function!(A, B, C, D, E, F, G, H);
+
+pub struct FunctionSystem<Marker, Func>
+where
+    Func: SystemParamFunction<Marker>,
+{
+    func: Func,
+    state: Option<<Func::Param as SystemParam>::State>,
+    access: SystemAccess,
+    last_run: Tick,
+    _marker: PhantomData<fn() -> Marker>,
+}
+
+impl<Marker, Func> System for FunctionSystem<Marker, Func>
+where
+    Marker: 'static,
+    Func: SystemParamFunction<Marker>,
+{
+    fn name(&self) -> &'static str {
+        type_name::<Func>()
+    }
+
+    fn initialize(&mut self, ecs: &mut ECS) {
+        let mut access = SystemAccess::new();
+        Func::Param::update_access(&mut access);
+        self.access = access;
+        self.state = Some(Func::Param::init_state(ecs));
+    }
+
+    fn access(&self) -> &SystemAccess {
+        &self.access
+    }
+
+    unsafe fn run_unsafe(&mut self, ecs: &ECS) {
+        let state = self
+            .state
+            .as_mut()
+            .unwrap_or_else(|| panic!("{} ran before being initialized", type_name::<Func>()));
+        let this_run = ecs.store.increment_change_tick();
+        let param = Func::Param::get_param(state, ecs, self.last_run, this_run);
+        self.func.run(param);
+        self.last_run = this_run;
+    }
+
+    fn run(&mut self, ecs: &mut ECS) {
+        // SAFETY: the world is borrowed exclusively and `initialize` checked the parameters
+        // against each other
+        unsafe { self.run_unsafe(ecs) }
+    }
+}
+
+/// A function that takes the whole `ECS`, it can't run alongside anything else
+pub struct ExclusiveSystem<Func> {
+    func: Func,
+    access: SystemAccess,
+}
+
+impl<Func> System for ExclusiveSystem<Func>
+where
+    Func: FnMut(&mut ECS) + Send + Sync + 'static,
+{
+    fn name(&self) -> &'static str {
+        type_name::<Func>()
+    }
+
+    fn initialize(&mut self, _ecs: &mut ECS) {}
+
+    fn access(&self) -> &SystemAccess {
+        &self.access
+    }
+
+    unsafe fn run_unsafe(&mut self, _ecs: &ECS) {
+        panic!(
+            "{} needs the whole ECS and can't run alongside other systems",
+            self.name()
+        );
+    }
+
+    fn run(&mut self, ecs: &mut ECS) {
+        (self.func)(ecs);
+    }
+}
+
+pub trait IntoSystem<Marker> {
+    type System: System + 'static;
+
+    fn into_system(self) -> Self::System;
+}
+
+pub struct IsFunctionSystem;
+
+pub struct IsExclusiveSystem;
+
+impl<Marker, Func> IntoSystem<(IsFunctionSystem, Marker)> for Func
+where
+    Marker: 'static,
+    Func: SystemParamFunction<Marker>,
+{
+    type System = FunctionSystem<Marker, Func>;
+
+    fn into_system(self) -> Self::System {
+        FunctionSystem {
+            func: self,
+            state: None,
+            access: SystemAccess::new(),
+            last_run: Tick::default(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<Func> IntoSystem<IsExclusiveSystem> for Func
+where
+    Func: FnMut(&mut ECS) + Send + Sync + 'static,
+{
+    type System = ExclusiveSystem<Func>;
+
+    fn into_system(self) -> Self::System {
+        ExclusiveSystem {
+            func: self,
+            access: SystemAccess::exclusive(),
+        }
+    }
 }
@@ -0,0 +1,101 @@
+use std::ops::{Deref, DerefMut};
+
+/// A point in time according to the `ComponentStorage` change counter.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Tick(u32);
+
+impl Tick {
+    pub fn new(tick: u32) -> Self {
+        Self(tick)
+    }
+
+    pub fn get(self) -> u32 {
+        self.0
+    }
+
+    pub fn next(self) -> Self {
+        Self(self.0.wrapping_add(1))
+    }
+
+    /// True if this tick happened after `last_run`, measured relative to `this_run` so the
+    /// comparison survives the counter wrapping around
+    pub fn is_newer_than(self, last_run: Tick, this_run: Tick) -> bool {
+        let since_change = this_run.0.wrapping_sub(self.0);
+        let since_last_run = this_run.0.wrapping_sub(last_run.0);
+        since_last_run > since_change
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ComponentTicks {
+    pub added: Tick,
+    pub changed: Tick,
+}
+
+impl ComponentTicks {
+    pub fn new(tick: Tick) -> Self {
+        Self {
+            added: tick,
+            changed: tick,
+        }
+    }
+
+    pub fn is_added(&self, last_run: Tick, this_run: Tick) -> bool {
+        self.added.is_newer_than(last_run, this_run)
+    }
+
+    pub fn is_changed(&self, last_run: Tick, this_run: Tick) -> bool {
+        self.changed.is_newer_than(last_run, this_run)
+    }
+}
+
+/// Mutable access to a component handed out by a query. Only writing through it marks the
+/// component as changed, so reading a `&mut` query item doesn't trip `Changed<T>` filters.
+pub struct Mut<'w, T> {
+    value: &'w mut T,
+    ticks: &'w mut ComponentTicks,
+    this_run: Tick,
+}
+
+impl<'w, T> Mut<'w, T> {
+    pub fn new(value: &'w mut T, ticks: &'w mut ComponentTicks, this_run: Tick) -> Self {
+        Self {
+            value,
+            ticks,
+            this_run,
+        }
+    }
+
+    pub fn is_added(&self, last_run: Tick) -> bool {
+        self.ticks.is_added(last_run, self.this_run)
+    }
+
+    pub fn is_changed(&self, last_run: Tick) -> bool {
+        self.ticks.is_changed(last_run, self.this_run)
+    }
+
+    /// Writes without marking the component as changed
+    pub fn bypass_change_detection(&mut self) -> &mut T {
+        self.value
+    }
+
+    pub fn into_inner(self) -> &'w mut T {
+        self.ticks.changed = self.this_run;
+        self.value
+    }
+}
+
+impl<'w, T> Deref for Mut<'w, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+impl<'w, T> DerefMut for Mut<'w, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.ticks.changed = self.this_run;
+        self.value
+    }
+}
@@ -0,0 +1,8 @@
+use crate::ecs::Entity;
+
+/// A player collided with a piece of food
+#[derive(Clone, Copy, Debug)]
+pub struct Eaten {
+    pub food: Entity,
+    pub by: Entity,
+}
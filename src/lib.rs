@@ -1,7 +1,11 @@
+// Lets derives name things as `::oxygin::...` both in here and downstream
+extern crate self as oxygin;
+
 pub mod app;
 pub mod bundles;
 pub mod components;
 pub mod ecs;
+pub mod events;
 pub mod render;
 pub mod resources;
 pub mod systems;
@@ -1,44 +1,58 @@
 use oxygin::app::app::App;
-use oxygin::bundles::{FoodBundle, PlayerBundle};
+use oxygin::bundles::PlayerBundle;
+use oxygin::components::{Edible, Gravity, InputState, Physics, Position, Render, Size, Velocity};
+use oxygin::ecs::{IntoSystemConfig, Stage};
+use oxygin::events::Eaten;
 use oxygin::render::asset_manager::{Food, Square};
-use oxygin::resources::Player;
+use oxygin::resources::Score;
+use oxygin::systems::{
+    apply_velocity, detect_eaten, gravity, grow, handle_input_system, move_system, relocate_eaten,
+    score, spawn_edible,
+};
 
 // TO IMPROVE
-// I want to remove the call to get ref then get component DONE!!!!!
-// Have a way to get the player entity more easily DONE!!! through resource storage
-// Break structure project to seperate out engine DONE
-// Add components to entities (if only syntactically) previously skill issued but now done
-// Remove refcell from component storage (Skilled issued -> Now fookin done (with anymap)
-//
 // maybe chainable (Im thinking plugins for this)
 // Traces within the ECS rather than unwraps
-//
-// investigate queries previous attempt go skill issued
-// Investigate component / resource register
 // Investigate plugins
-//
 
-//
-// Query 2nd attempt
-// Given query i want to get data do some computation with the data
-// example:
-// pub fun move_system(Query<(T1: QueryData, T2: QueryData)>
-// Command -> update the data
+fn init(app: &mut App) -> eyre::Result<()> {
+    app.ecs.register_scene_component::<Position>();
+    app.ecs.register_scene_component::<Velocity>();
+    app.ecs.register_scene_component::<Gravity>();
+    app.ecs.register_scene_component::<Size>();
+    app.ecs.register_scene_component::<InputState>();
+    app.ecs.register_scene_component::<Physics>();
+    app.ecs.register_scene_component::<Edible>();
+    app.ecs.register_scene_component::<Render>();
 
-fn init(app: &mut App) {
-    app.ecs.register_bundle::<FoodBundle>();
-    app.ecs.register_bundle::<PlayerBundle>();
-
-    let player = app
-        .ecs
-        .add_bundle(PlayerBundle::new(400, 400, 50, 0.5, 0.5, 0.5))
-        .unwrap();
-    app.ecs.add_resource(Player::new(&player));
+    // Before anything with a `Render` is spawned, the models are looked up on insert
     let square = Square::new();
     let square2 = Food::new();
     app.register_asset(square);
     app.register_asset(square2);
-    app.assets.add_asset::<Square>(player);
+    let player = app
+        .ecs
+        .add_bundle(PlayerBundle::new(400, 400, 50, 0.5, 0.5, 0.5))?;
+    app.ecs.init_resource::<Score>();
+    app.add_event::<Eaten>();
+    // An eye in the player's corner, attached so it moves with them
+    let eye = app
+        .ecs
+        .spawn()
+        .with(Render::new(0.4, 0.4, 0.0, 0.2).with_model::<Food>())
+        .id();
+    app.ecs.set_parent(eye, player)?;
+
+    app.add_system(Stage::PreUpdate, handle_input_system);
+    app.add_system(Stage::FixedUpdate, move_system);
+    app.add_system(Stage::FixedUpdate, spawn_edible.after(move_system));
+    app.add_system(Stage::FixedUpdate, gravity);
+    app.add_system(Stage::FixedUpdate, apply_velocity.after(gravity));
+    app.add_system(Stage::FixedUpdate, detect_eaten.after(apply_velocity));
+    app.add_system(Stage::FixedUpdate, grow.after(detect_eaten));
+    app.add_system(Stage::FixedUpdate, score.after(detect_eaten));
+    app.add_system(Stage::FixedUpdate, relocate_eaten.after(detect_eaten));
+    Ok(())
 }
 
 fn main() {
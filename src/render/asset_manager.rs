@@ -2,7 +2,10 @@ use std::{any::TypeId, collections::HashMap};
 
 use nalgebra::Vector3;
 
-use crate::ecs::Entity;
+use crate::{
+    components::{GlobalTransform, Render},
+    ecs::{Entity, ECS},
+};
 
 use super::InstanceRaw;
 
@@ -43,27 +46,10 @@ impl Model {
     }
 }
 
-pub struct ReCalculate(bool);
-
-impl ReCalculate {
-    pub fn new() -> Self {
-        Self(true)
-    }
-    pub fn inner(&self) -> &bool {
-        &self.0
-    }
-    pub fn moved(&mut self) {
-        self.0 = true;
-    }
-    pub fn finish(&mut self) {
-        self.0 = false;
-    }
-}
-
 pub struct InstanceContainer {
     pub instances: Vec<InstanceRaw>,
     pub entity: Vec<Entity>,
-    pub stale: Vec<ReCalculate>,
+    pub index: HashMap<Entity, usize>,
 }
 
 pub struct AssetInstance {
@@ -72,12 +58,18 @@ pub struct AssetInstance {
     pub stale: bool,
 }
 
+impl Default for InstanceContainer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl InstanceContainer {
     pub fn new() -> Self {
         Self {
             instances: Vec::new(),
             entity: Vec::new(),
-            stale: Vec::new(),
+            index: HashMap::new(),
         }
     }
 }
@@ -86,6 +78,14 @@ pub struct AssetManager {
     pub assets: Vec<Renderable>,
     pub instances: HashMap<TypeId, InstanceContainer>,
     pub asset_instance: HashMap<TypeId, Vec<AssetInstance>>,
+    // Model type names to their ids, for `Render::model`
+    names: HashMap<&'static str, TypeId>,
+}
+
+impl Default for AssetManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl AssetManager {
@@ -94,32 +94,63 @@ impl AssetManager {
             assets: Vec::new(),
             instances: HashMap::new(),
             asset_instance: HashMap::new(),
+            names: HashMap::new(),
         }
     }
 
-    pub fn register(&mut self, model: Model, id: TypeId) {
+    pub fn register(&mut self, model: Model, id: TypeId, name: &'static str) {
         self.assets.push(Renderable::new(model, id));
         self.instances.insert(id, InstanceContainer::new());
+        self.names.insert(name, id);
     }
 
     pub fn add_asset<T>(&mut self, entity: Entity)
     where
         T: Into<Model> + 'static,
     {
-        let instance = self.instances.get_mut(&TypeId::of::<T>()).unwrap();
+        self.add_instance(TypeId::of::<T>(), entity);
+    }
+
+    /// Draws the entity with the model registered under `name` and nothing else, starting
+    /// from `raw`. Already being drawn with it leaves the instance as it is
+    pub fn set_model(&mut self, name: &str, entity: Entity, raw: InstanceRaw) {
+        let id = self.names.get(name).copied();
+        let drawn = id.and_then(|id| self.instances.get(&id));
+        if drawn.is_some_and(|instance| instance.index.contains_key(&entity)) {
+            return;
+        }
+        self.remove_entity(entity);
+        if let Some(id) = id {
+            self.add_instance(id, entity);
+            self.update_instance(entity, raw);
+        }
+    }
+
+    fn add_instance(&mut self, id: TypeId, entity: Entity) {
+        let instance = self.instances.get_mut(&id).unwrap();
+        instance.index.insert(entity, instance.entity.len());
         instance.instances.push(InstanceRaw::default());
-        instance.stale.push(ReCalculate::new());
         instance.entity.push(entity);
     }
 
-    pub fn mark_instance_change<T>(&mut self, entity: Entity)
-    where
-        T: Into<Model> + 'static,
-    {
-        let instance = self.instances.get_mut(&TypeId::of::<T>()).unwrap();
-        for i in 0..instance.entity.len() {
-            if instance.entity[i] == entity {
-                instance.stale[i].moved();
+    /// Overwrites the entity's instance data for every model it was added to
+    pub fn update_instance(&mut self, entity: Entity, raw: InstanceRaw) {
+        for instance in self.instances.values_mut() {
+            if let Some(i) = instance.index.get(&entity) {
+                instance.instances[*i] = raw;
+            }
+        }
+    }
+
+    /// Stops drawing the entity with every model it was added to
+    pub fn remove_entity(&mut self, entity: Entity) {
+        for instance in self.instances.values_mut() {
+            if let Some(i) = instance.index.remove(&entity) {
+                instance.instances.swap_remove(i);
+                instance.entity.swap_remove(i);
+                if let Some(moved) = instance.entity.get(i) {
+                    instance.index.insert(*moved, i);
+                }
             }
         }
     }
@@ -129,6 +160,33 @@ impl AssetManager {
     }
 }
 
+/// Keeps the `AssetManager` resource in step with every `Render`, inserting one draws the
+/// entity with its model and removing it or despawning stops drawing it
+pub fn add_render_hooks(ecs: &mut ECS) {
+    // Every insert, so swapping the `Render` for one with another model moves the entity
+    ecs.store.on_insert::<Render>(|ecs, entity| {
+        let Ok(render) = ecs.query::<Render>(entity) else {
+            return;
+        };
+        let model = render.model.clone();
+        // The renderer only uploads transforms that change, so a moved instance has to start
+        // out where the entity already is
+        let raw = ecs
+            .query::<GlobalTransform>(entity)
+            .cloned()
+            .unwrap_or_default()
+            .to_raw();
+        if let Some(assets) = ecs.get_mut_resource::<AssetManager>() {
+            assets.set_model(&model, entity, raw);
+        }
+    });
+    ecs.store.on_remove::<Render>(|ecs, entity| {
+        if let Some(assets) = ecs.get_mut_resource::<AssetManager>() {
+            assets.remove_entity(entity);
+        }
+    });
+}
+
 pub trait Vertex: Copy + Clone + std::fmt::Debug + bytemuck::Pod + bytemuck::Zeroable {
     const ATTRIBS: &'static [wgpu::VertexAttribute];
     fn desc() -> wgpu::VertexBufferLayout<'static> {
@@ -137,7 +195,7 @@ pub trait Vertex: Copy + Clone + std::fmt::Debug + bytemuck::Pod + bytemuck::Zer
         wgpu::VertexBufferLayout {
             array_stride: mem::size_of::<Self>() as wgpu::BufferAddress,
             step_mode: wgpu::VertexStepMode::Vertex,
-            attributes: &Self::ATTRIBS,
+            attributes: Self::ATTRIBS,
         }
     }
 }
@@ -160,6 +218,12 @@ pub struct Square {
     pub indicies: Vec<u16>,
 }
 
+impl Default for Square {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Square {
     pub fn new() -> Self {
         Self {
@@ -186,11 +250,11 @@ impl Square {
     }
 }
 
-impl Into<Model> for Square {
-    fn into(self) -> Model {
+impl From<Square> for Model {
+    fn from(model: Square) -> Self {
         Model {
-            vertices: self.vertices,
-            indicies: self.indicies,
+            vertices: model.vertices,
+            indicies: model.indicies,
         }
     }
 }
@@ -201,6 +265,12 @@ pub struct Food {
     pub indicies: Vec<u16>,
 }
 
+impl Default for Food {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Food {
     pub fn new() -> Self {
         Self {
@@ -227,11 +297,11 @@ impl Food {
     }
 }
 
-impl Into<Model> for Food {
-    fn into(self) -> Model {
+impl From<Food> for Model {
+    fn from(model: Food) -> Self {
         Model {
-            vertices: self.vertices,
-            indicies: self.indicies,
+            vertices: model.vertices,
+            indicies: model.indicies,
         }
     }
 }
@@ -8,7 +8,10 @@ use wgpu::{
 };
 use winit::{dpi::PhysicalSize, window::Window};
 
-use crate::{components::Render, ecs::ECS};
+use crate::{
+    components::GlobalTransform,
+    ecs::{Changed, Entity, Query, Tick, ECS},
+};
 
 use super::{
     asset_manager::{AssetManager, Model},
@@ -55,6 +58,7 @@ pub struct RenderState<'render> {
     pub staging_capacity: usize,
     pub num_vertices: u32,
     pub num_indices: u32,
+    pub last_render_tick: Tick,
 }
 impl<'render> RenderState<'render> {
     pub fn new(window: Arc<Window>) -> RenderState<'render> {
@@ -84,6 +88,7 @@ impl<'render> RenderState<'render> {
             staging_capacity,
             num_vertices,
             num_indices,
+            last_render_tick: Tick::default(),
         }
     }
 
@@ -122,11 +127,7 @@ impl<'render> RenderState<'render> {
     // TODO: Investigate using vec2 instead of vec 3 with a seperate float for depth
     // TODO Investigate world space
     //
-    pub fn render(
-        &mut self,
-        game: &mut ECS,
-        assets: &mut AssetManager,
-    ) -> Result<(), wgpu::SurfaceError> {
+    pub fn render(&mut self, game: &mut ECS) -> Result<(), wgpu::SurfaceError> {
         let output = self.surface.get_current_texture()?;
         let view = output
             .texture
@@ -137,7 +138,11 @@ impl<'render> RenderState<'render> {
                 label: Some("Render Encoder"),
             });
 
-        self.update_instance_data(game, assets);
+        let changed = self.changed_instances(game);
+        let assets = game.get_mut_resource::<AssetManager>().unwrap();
+        for (entity, raw) in changed {
+            assets.update_instance(entity, raw);
+        }
 
         self.update_buffer_capacity(assets);
 
@@ -152,7 +157,7 @@ impl<'render> RenderState<'render> {
                 &model_buff.instance,
                 &mut encoder,
                 &mut self.staging_belt,
-                &mut self.device,
+                &self.device,
             );
 
             model_buffs.push(model_buff);
@@ -177,7 +182,7 @@ impl<'render> RenderState<'render> {
         Ok(())
     }
 
-    fn update_buffer_capacity<'a>(&mut self, asset_manager: &AssetManager) {
+    fn update_buffer_capacity(&mut self, asset_manager: &AssetManager) {
         for renderable in asset_manager.assets.iter() {
             let model_buff = self.model_buffers.get_mut(&renderable.id).unwrap();
             let instances = asset_manager.get_instance_data(&renderable.id);
@@ -195,8 +200,7 @@ impl<'render> RenderState<'render> {
     }
 
     fn create_bigger_staging_buffer(&mut self, new_capacity: usize) -> StagingBelt {
-        let new_belt = StagingBelt::new((InstanceRaw::size() * new_capacity) as u64);
-        new_belt
+        StagingBelt::new((InstanceRaw::size() * new_capacity) as u64)
     }
 
     fn write_staging_buff(
@@ -221,11 +225,11 @@ impl<'render> RenderState<'render> {
         &'b self,
         view: &'b wgpu::TextureView,
         encoder: &'b mut CommandEncoder,
-    ) -> Result<RenderPass, wgpu::SurfaceError> {
+    ) -> Result<RenderPass<'b>, wgpu::SurfaceError> {
         Ok(encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("Render Pass"),
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: &view,
+                view,
                 resolve_target: None,
                 ops: wgpu::Operations {
                     load: wgpu::LoadOp::Clear(wgpu::Color {
@@ -243,19 +247,18 @@ impl<'render> RenderState<'render> {
         }))
     }
 
-    fn update_instance_data(&self, ecs: &mut ECS, assets_manager: &mut AssetManager) {
-        for asset in assets_manager.assets.iter() {
-            let model = assets_manager.instances.get_mut(&asset.id).unwrap();
-            assert!(model.instances.len() == model.entity.len());
-            assert!(model.instances.len() == model.stale.len());
-            for i in 0..model.instances.len() {
-                if *model.stale[i].inner() {
-                    let instance_component = ecs.query::<Render>(model.entity[i]).unwrap();
-                    model.instances[i] = instance_component.to_raw();
-                    model.stale[i].finish();
-                }
-            }
-        }
+    // Only entities whose `GlobalTransform` changed since the last frame need their instance
+    // rebuilt, `propagate_transforms` has already folded in any parent that moved
+    fn changed_instances(&mut self, game: &ECS) -> Vec<(Entity, InstanceRaw)> {
+        let changed = Query::<&GlobalTransform, Changed<GlobalTransform>>::read_only_since(
+            game,
+            self.last_render_tick,
+        )
+        .iter()
+        .map(|(entity, global)| (entity, global.to_raw()))
+        .collect();
+        self.last_render_tick = game.store.increment_change_tick();
+        changed
     }
 
     pub fn register_new_buffer(&mut self, capacity: usize, renderable: &Model, type_id: TypeId) {
@@ -301,7 +304,7 @@ impl<'render> RenderState<'render> {
             label: Some("Render Pipeline"),
             layout: Some(&render_pipeline_layout),
             vertex: wgpu::VertexState {
-                module: &shader,
+                module: shader,
                 entry_point: "vs_main",
                 buffers: &[Vertex::desc(), InstanceRaw::desc()],
                 compilation_options: PipelineCompilationOptions::default(),
@@ -1,19 +1,11 @@
-use crate::ecs::Entity;
+use winit::event::KeyEvent;
 
-pub struct Player(Entity);
-
-impl Player {
-    pub fn new(entity: &Entity) -> Self {
-        Self(*entity)
-    }
-
-    pub fn as_ref(&self) -> &Entity {
-        &self.0
-    }
+/// Key presses from the window waiting to be handled by the next `PreUpdate`
+#[derive(Default)]
+pub struct KeyboardInput {
+    pub events: Vec<KeyEvent>,
 }
 
-impl Into<Entity> for &Player {
-    fn into(self) -> Entity {
-        self.0
-    }
-}
+/// How much food has been eaten so far
+#[derive(Default)]
+pub struct Score(pub u32);
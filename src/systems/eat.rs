@@ -1,92 +1,52 @@
 use rand::Rng;
 
 use crate::{
-    components::{Edible, Position, Size},
-    ecs::{Entity, ECS},
-    resources::Player,
+    components::{Edible, InputState, Position, Size},
+    ecs::{EventReader, EventWriter, Query, ResMut, With, Without},
+    events::Eaten,
+    resources::Score,
 };
 
-pub fn eat_system(game: &mut ECS) {
-    let eaten_entities = get_eaten_entities(game);
-
-    set_edible_eaten(game, &eaten_entities);
-
-    eat_edibles(game, &eaten_entities);
-
-    handle_eaten(game, &eaten_entities);
-
-    if eaten_entities.len() > 0 {
-        grow(game, &eaten_entities);
+#[allow(clippy::type_complexity)]
+pub fn detect_eaten(
+    eaters: Query<(&Position, &Size), With<InputState>>,
+    edibles: Query<(&Position, &Size), (With<Edible>, Without<InputState>)>,
+    mut eaten: EventWriter<Eaten>,
+) {
+    for (eater, (eater_position, eater_size)) in eaters.iter() {
+        for (food, (edible_position, edible_size)) in edibles.iter() {
+            if check_collision(eater_position, eater_size, edible_position, edible_size) {
+                eaten.send(Eaten { food, by: eater });
+            }
+        }
     }
 }
 
-fn grow(game: &mut ECS, entities: &Vec<Entity>) {
-    let player = game.resources.get::<Player>().unwrap().into();
-    let calories = get_eaten_calories(game, entities);
-    let size_component = game.query_mut::<Size>(player).unwrap();
-    size_component.size = size_component.size + calories;
-}
-
-fn get_eaten_calories(game: &ECS, entities: &Vec<Entity>) -> u32 {
-    let edible_component = game.get_component::<Edible>().unwrap();
-    entities.iter().fold(0, |a, eaten| {
-        let edible = edible_component.get(*eaten).unwrap();
-        a + edible.calories
-    })
-}
-
-fn set_edible_eaten(game: &mut ECS, entities: &Vec<Entity>) {
-    let edible_component = game.get_mut_component::<Edible>().unwrap();
-
-    entities.into_iter().for_each(|entity| {
-        let edible = edible_component.get_mut(*entity).unwrap();
-        edible.eaten = true;
-    })
-}
-
-fn get_eaten_entities(game: &mut ECS) -> Vec<Entity> {
-    let player = game.resources.get::<Player>().unwrap().into();
-    let position_component = game.get_component::<Position>().unwrap();
-    let size_component = game.get_component::<Size>().unwrap();
-    let edible_component = game.get_component::<Edible>().unwrap();
-    let mut eaten_entities: Vec<Entity> = vec![];
-
-    let player_position = position_component.get(player).unwrap();
-    let player_size = size_component.get(player).unwrap();
-
-    for edible_key in edible_component.keys() {
-        let edible_position = position_component.get(edible_key).unwrap();
-        let edible_size = size_component.get(edible_key).unwrap();
-        if check_collision(
-            &player_position,
-            &player_size,
-            &edible_position,
-            &edible_size,
-        ) {
-            eaten_entities.push(edible_key);
+pub fn grow(mut eaten: EventReader<Eaten>, edibles: Query<&Edible>, mut eaters: Query<&mut Size>) {
+    for event in eaten.read() {
+        let Some(edible) = edibles.get(event.food) else {
+            continue;
+        };
+        if let Some(mut size) = eaters.get_mut(event.by) {
+            size.size += edible.calories;
         }
     }
-
-    eaten_entities
 }
 
-fn handle_eaten(game: &mut ECS, entities: &Vec<Entity>) {
-    let edible_component = game.get_mut_component::<Edible>().unwrap();
-
-    entities.into_iter().for_each(|edible| {
-        let eaten_entity = edible_component.get_mut(*edible).unwrap();
-        eaten_entity.eaten = false;
-    })
+pub fn score(mut eaten: EventReader<Eaten>, mut score: ResMut<Score>) {
+    score.0 += eaten.read().count() as u32;
 }
 
-fn eat_edibles(game: &mut ECS, entities: &Vec<Entity>) {
-    let position_component = game.get_mut_component::<Position>().unwrap();
-
-    entities.into_iter().for_each(|entity| {
-        let position = position_component.get_mut(*entity).unwrap();
-        position.x = rand::thread_rng().gen_range(1..1920);
-        position.y = rand::thread_rng().gen_range(1..1080);
-    });
+pub fn relocate_eaten(
+    mut eaten: EventReader<Eaten>,
+    mut edibles: Query<&mut Position, With<Edible>>,
+) {
+    for event in eaten.read() {
+        if let Some(mut position) = edibles.get_mut(event.food) {
+            position.x = rand::thread_rng().gen_range(1..1920);
+            position.y = rand::thread_rng().gen_range(1..1080);
+        }
+    }
 }
 
 fn check_collision(position1: &Position, size1: &Size, position2: &Position, size2: &Size) -> bool {
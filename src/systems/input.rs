@@ -5,14 +5,24 @@ use winit::{
     platform::modifier_supplement::KeyEventExtModifierSupplement,
 };
 
-use crate::{components::InputState, ecs::ECS, resources::Player};
-
-pub fn handle_input_system(event: &KeyEvent, game: &mut ECS) {
-    let player = game.resources.get::<Player>().unwrap().into();
-    let input_component = game.get_mut_component::<InputState>().unwrap();
+use crate::{
+    components::InputState,
+    ecs::{Query, ResMut},
+    resources::KeyboardInput,
+};
 
-    let input = input_component.get_mut(player).unwrap();
+pub fn handle_input_system(
+    mut keyboard: ResMut<KeyboardInput>,
+    mut inputs: Query<&mut InputState>,
+) {
+    for event in keyboard.events.drain(..) {
+        for (_, mut input) in inputs.iter_mut() {
+            apply_key_event(&event, &mut input);
+        }
+    }
+}
 
+fn apply_key_event(event: &KeyEvent, input: &mut InputState) {
     if event.state == ElementState::Pressed {
         match event.key_without_modifiers().as_ref() {
             Key::Named(NamedKey::ArrowUp) => {
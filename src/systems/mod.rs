@@ -8,3 +8,5 @@ pub mod gravity;
 pub mod spawn;
 pub use gravity::*;
 pub use spawn::*;
+pub mod transform;
+pub use transform::*;
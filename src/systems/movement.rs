@@ -1,57 +1,33 @@
 use crate::{
     components::{InputState, Physics, Render},
-    ecs::{Entity, ECS},
-    render::asset_manager::{self, AssetManager, Square},
-    resources::Player,
+    ecs::Query,
 };
 
-pub fn move_system(game: &mut ECS, asset_manager: &mut AssetManager) {
-    let player = game.resources.get::<Player>().unwrap().into();
-    let (input, speed) = gather_data(player, game);
-    let position_component = game.get_mut_component::<Render>().unwrap();
-    let mut moved = false;
-
-    if let Some(player) = position_component.get_mut(player) {
+pub fn move_system(mut players: Query<(&InputState, &Physics, &mut Render)>) {
+    for (_, (input, speed, mut render)) in players.iter_mut() {
         if input.up {
-            player.transform.y = player.transform.y + speed.speed;
-            if player.transform.y > 1.0 {
-                player.transform.y = 1.0;
+            render.transform.y += speed.speed;
+            if render.transform.y > 1.0 {
+                render.transform.y = 1.0;
             }
-            moved = true;
         }
         if input.right {
-            player.transform.x = player.transform.x + speed.speed;
-            if player.transform.x > 1.0 {
-                player.transform.x = 1.0;
+            render.transform.x += speed.speed;
+            if render.transform.x > 1.0 {
+                render.transform.x = 1.0;
             }
-            moved = true;
         }
         if input.down {
-            player.transform.y = player.transform.y - speed.speed;
-            if player.transform.y < -1.0 {
-                player.transform.y = -1.0;
+            render.transform.y -= speed.speed;
+            if render.transform.y < -1.0 {
+                render.transform.y = -1.0;
             }
-            moved = true;
         }
         if input.left {
-            player.transform.x = player.transform.x - speed.speed;
-            if player.transform.x < -1.0 {
-                player.transform.x = -1.0;
+            render.transform.x -= speed.speed;
+            if render.transform.x < -1.0 {
+                render.transform.x = -1.0;
             }
-            moved = true;
         }
     }
-
-    if moved {
-        asset_manager.mark_instance_change::<Square>(player);
-    }
-}
-
-pub fn gather_data(player: Entity, game: &ECS) -> (InputState, Physics) {
-    let input_component = game.get_component::<InputState>().unwrap();
-    let physics_component = game.get_component::<Physics>().unwrap();
-    (
-        input_component.get(player).unwrap().clone(),
-        physics_component.get(player).unwrap().clone(),
-    )
 }
@@ -1,19 +1,18 @@
 use crate::{
     bundles::FoodBundle,
     components::InputState,
-    ecs::ECS,
-    render::asset_manager::{AssetManager, Food},
-    resources::Player,
+    ecs::{Commands, Query},
 };
 
-pub fn spawn_edible(game: &mut ECS, asset_manager: &mut AssetManager) {
-    let player = game.resources.get::<Player>().unwrap().into();
-    let input = game.query_mut::<InputState>(player).unwrap();
+pub fn spawn_edible(mut commands: Commands, inputs: Query<&InputState>) {
+    // Only the player takes input
+    let Ok((_, input)) = inputs.single() else {
+        return;
+    };
 
     if input.space {
-        for i in 0..1000 {
-            let food = game.add_bundle(FoodBundle::new()).unwrap();
-            asset_manager.add_asset::<Food>(food)
+        for _ in 0..1000 {
+            commands.spawn(FoodBundle::new());
         }
     }
 }
@@ -0,0 +1,54 @@
+use nalgebra::Matrix4;
+
+use crate::{
+    components::{GlobalTransform, Render},
+    ecs::{Children, Entity, Parent, ECS},
+};
+
+/// Walks every tree from its root and combines the local `Render` transforms into a
+/// `GlobalTransform`. Entities without a `Render` pass their parent's transform straight
+/// through. Only transforms that actually moved are written so the renderer's change
+/// detection still skips the rest
+pub fn propagate_transforms(ecs: &mut ECS) {
+    let (Ok(renders), Ok(parents), Ok(children), Ok(globals)) = (
+        ecs.store.get::<Render>(),
+        ecs.store.get::<Parent>(),
+        ecs.store.get::<Children>(),
+        ecs.store.get::<GlobalTransform>(),
+    ) else {
+        return;
+    };
+
+    let mut stack: Vec<(Entity, Matrix4<f32>)> = renders
+        .keys()
+        .chain(
+            children
+                .keys()
+                .filter(|entity| !renders.contains_key(*entity)),
+        )
+        .filter(|entity| !parents.contains_key(*entity))
+        .map(|entity| (entity, Matrix4::identity()))
+        .collect();
+
+    let mut moved = Vec::new();
+    while let Some((entity, parent_matrix)) = stack.pop() {
+        let matrix = match renders.get(entity) {
+            Some(render) => {
+                let matrix = parent_matrix * render.local_matrix();
+                if globals.get(entity).map(|global| global.matrix) != Some(matrix) {
+                    moved.push((entity, matrix));
+                }
+                matrix
+            }
+            None => parent_matrix,
+        };
+        if let Some(children) = children.get(entity) {
+            stack.extend(children.iter().map(|child| (child, matrix)));
+        }
+    }
+
+    for (entity, matrix) in moved {
+        // Every entity here came out of a live column so it can't be dead
+        let _ = ecs.store.insert(entity, GlobalTransform { matrix });
+    }
+}
@@ -0,0 +1,97 @@
+use oxygin::{
+    components::{Position, Size, Velocity},
+    ecs::ArchetypeStorage,
+};
+
+#[test]
+fn inserting_moves_the_row_to_the_new_archetype() {
+    let mut storage = ArchetypeStorage::new();
+    let [a, b] = [(); 2].map(|_| storage.create());
+    storage.insert(a, Position::new(1, 1));
+    storage.insert(b, Position::new(2, 2));
+    let before = storage.location(b).unwrap();
+
+    // `a` leaves the position-only archetype, `b` is swapped into its row
+    storage.insert(a, Velocity::new(3, 3));
+    let moved = storage.location(a).unwrap();
+    assert_ne!(moved.archetype, before.archetype);
+    assert_eq!(moved.row, 0);
+    assert_eq!(storage.location(b).unwrap().row, 0);
+    assert_eq!(storage.get::<Position>(a).unwrap().x, 1);
+    assert_eq!(storage.get::<Velocity>(a).unwrap().vx, 3);
+    assert_eq!(storage.get::<Position>(b).unwrap().x, 2);
+
+    let replaced = storage.insert(a, Position::new(4, 4)).unwrap();
+    assert_eq!(replaced.x, 1);
+    assert_eq!(storage.location(a).unwrap().archetype, moved.archetype);
+}
+
+#[test]
+fn removing_moves_the_row_back_and_hands_the_value_over() {
+    let mut storage = ArchetypeStorage::new();
+    let [a, b] = [(); 2].map(|_| storage.create());
+    for (entity, x) in [(a, 1), (b, 2)] {
+        storage.insert(entity, Position::new(x, x));
+        storage.insert(entity, Velocity::new(x, x));
+    }
+    let position_only = {
+        let c = storage.create();
+        storage.insert(c, Position::new(0, 0));
+        storage.location(c).unwrap().archetype
+    };
+
+    assert_eq!(storage.remove::<Velocity>(a).unwrap().vx, 1);
+    assert!(storage.remove::<Velocity>(a).is_none());
+    assert_eq!(storage.location(a).unwrap().archetype, position_only);
+    assert_eq!(storage.location(b).unwrap().row, 0);
+    assert_eq!(storage.get::<Position>(a).unwrap().x, 1);
+    assert!(storage.get::<Velocity>(a).is_none());
+    assert_eq!(storage.get::<Velocity>(b).unwrap().vx, 2);
+}
+
+#[test]
+fn despawning_swaps_the_last_row_into_the_gap() {
+    let mut storage = ArchetypeStorage::new();
+    let [a, b, c] = [(); 3].map(|_| storage.create());
+    for (entity, size) in [(a, 1), (b, 2), (c, 3)] {
+        storage.insert(entity, Size::new(size));
+    }
+
+    assert!(storage.despawn(a));
+    assert!(!storage.despawn(a));
+    assert!(storage.location(a).is_none());
+    assert_eq!(storage.location(c).unwrap().row, 0);
+    assert_eq!(storage.location(b).unwrap().row, 1);
+    assert_eq!(storage.get::<Size>(c).unwrap().size, 3);
+    assert_eq!(storage.get::<Size>(b).unwrap().size, 2);
+}
+
+#[test]
+fn queries_follow_entities_between_archetypes() {
+    let mut storage = ArchetypeStorage::new();
+    let [a, b, c] = [(); 3].map(|_| storage.create());
+    for entity in [a, b, c] {
+        storage.insert(entity, Position::new(0, 0));
+        storage.insert(entity, Velocity::new(1, 2));
+    }
+    storage.insert(b, Size::new(1));
+    storage.remove::<Velocity>(c);
+    storage.despawn(a);
+
+    let mut moved = Vec::new();
+    for (entity, (position, velocity)) in storage.query::<(&mut Position, &Velocity)>() {
+        position.y += velocity.vy;
+        moved.push(entity);
+    }
+    assert_eq!(moved, [b]);
+    assert_eq!(storage.get::<Position>(b).unwrap().y, 2);
+    assert_eq!(storage.get::<Position>(c).unwrap().y, 0);
+    assert_eq!(storage.query::<&Position>().count(), 2);
+}
+
+#[test]
+#[should_panic(expected = "conflicts with")]
+fn aliased_table_queries_panic() {
+    let mut storage = ArchetypeStorage::new();
+    let _ = storage.query::<(&mut Position, &Position)>().count();
+}
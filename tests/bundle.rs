@@ -0,0 +1,105 @@
+use oxygin::{
+    bundles::{FoodBundle, PhysicsBundle},
+    components::{Gravity, Position, Size, Velocity},
+    ecs::{EcsError, ECS},
+};
+
+// Nothing imported besides what's spelled out, so the derive has to use full paths
+mod derived {
+    #[derive(oxygin::ecs::Bundle)]
+    pub struct Pair(
+        pub oxygin::components::Position,
+        pub oxygin::components::Size,
+    );
+
+    #[derive(oxygin::ecs::Bundle)]
+    pub struct Wrap<T> {
+        pub inner: T,
+        #[bundle]
+        pub pair: Pair,
+        #[bundle(skip)]
+        pub note: String,
+    }
+
+    #[derive(oxygin::ecs::Bundle)]
+    pub struct Empty;
+}
+
+use derived::{Empty, Pair, Wrap};
+
+#[test]
+fn nested_generic_and_skipped_fields() {
+    let mut ecs = ECS::new();
+    ecs.register_bundle::<Wrap<Velocity>>();
+    let wrap = Wrap {
+        inner: Velocity::new(1, 2),
+        pair: Pair(Position::new(3, 4), Size::new(5)),
+        note: "not a component".into(),
+    };
+    assert_eq!(wrap.note, "not a component");
+    let entity = ecs.add_bundle(wrap).unwrap();
+    assert_eq!(ecs.query::<Velocity>(entity).unwrap().vy, 2);
+    assert_eq!(ecs.query::<Position>(entity).unwrap().y, 4);
+    assert_eq!(ecs.query::<Size>(entity).unwrap().size, 5);
+}
+
+#[test]
+fn tuple_and_unit_bundles() {
+    let mut ecs = ECS::new();
+    ecs.register_bundle::<Pair>();
+    ecs.register_bundle::<Empty>();
+    let pair = ecs
+        .add_bundle(Pair(Position::new(0, 0), Size::new(1)))
+        .unwrap();
+    assert_eq!(ecs.query::<Size>(pair).unwrap().size, 1);
+    let empty = ecs.add_bundle(Empty).unwrap();
+    assert!(ecs.store.contains(empty));
+}
+
+#[test]
+fn bundles_register_their_columns_on_first_spawn() {
+    let mut ecs = ECS::new();
+    let entity = ecs.add_bundle((Velocity::new(1, 1), Size::new(2))).unwrap();
+    assert_eq!(ecs.query::<Size>(entity).unwrap().size, 2);
+    assert_eq!(ecs.get_component::<Velocity>().unwrap().len(), 1);
+    // A second spawn finds the columns already there
+    ecs.add_bundle((Velocity::new(2, 2), Size::new(3))).unwrap();
+    assert_eq!(ecs.get_component::<Size>().unwrap().len(), 2);
+}
+
+#[test]
+fn insert_and_remove_bundles_on_live_entities() {
+    let mut ecs = ECS::new();
+    let entity = ecs.create_entity();
+    ecs.insert_bundle(entity, (Position::new(1, 1), Size::new(2)))
+        .unwrap();
+    ecs.insert_bundle(entity, PhysicsBundle::default()).unwrap();
+    assert!(ecs.query::<Gravity>(entity).is_ok());
+    assert!(ecs.query::<Velocity>(entity).is_ok());
+
+    ecs.remove_bundle::<PhysicsBundle>(entity).unwrap();
+    assert!(ecs.query::<Gravity>(entity).is_err());
+    assert!(ecs.query::<Velocity>(entity).is_err());
+    assert_eq!(ecs.query::<Size>(entity).unwrap().size, 2);
+
+    // Nested bundles come off too, and the components it never had are skipped
+    ecs.remove_bundle::<FoodBundle>(entity).unwrap();
+    assert!(ecs.query::<Position>(entity).is_err());
+    assert!(ecs.query::<Size>(entity).is_err());
+    assert!(ecs.store.contains(entity));
+}
+
+#[test]
+fn bundles_need_a_live_entity() {
+    let mut ecs = ECS::new();
+    let entity = ecs.create_entity();
+    ecs.despawn(entity).unwrap();
+    assert_eq!(
+        ecs.insert_bundle(entity, (Size::new(1),)),
+        Err(EcsError::DeadEntity(entity))
+    );
+    assert_eq!(
+        ecs.remove_bundle::<(Size,)>(entity),
+        Err(EcsError::DeadEntity(entity))
+    );
+}
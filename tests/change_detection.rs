@@ -0,0 +1,94 @@
+use oxygin::{
+    components::{Position, Size},
+    ecs::{Added, Changed, Children, Component, Entity, Query, Tick, ECS},
+};
+
+// Two positioned entities, with every change tick after the returned one still to come
+fn world() -> (ECS<'static>, [Entity; 2], Tick) {
+    let mut ecs = ECS::new();
+    let a = ecs.spawn().with(Position::new(0, 0)).id();
+    let b = ecs.spawn().with(Position::new(0, 0)).id();
+    let since = ecs.store.increment_change_tick();
+    ecs.store.increment_change_tick();
+    (ecs, [a, b], since)
+}
+
+fn changed_since<T: Component + 'static>(ecs: &ECS, since: Tick) -> Vec<Entity> {
+    Query::<&T, Changed<T>>::read_only_since(ecs, since)
+        .iter()
+        .map(|(entity, _)| entity)
+        .collect()
+}
+
+#[test]
+fn only_writes_through_mut_count_as_changes() {
+    let (mut ecs, [a, _], since) = world();
+    let mut query = Query::<&mut Position>::new(&mut ecs);
+    for (entity, mut position) in query.iter_mut() {
+        if entity == a {
+            position.x = 5;
+        } else {
+            assert_eq!(position.x, 0);
+        }
+    }
+    assert_eq!(changed_since::<Position>(&ecs, since), [a]);
+}
+
+#[test]
+fn bypassing_change_detection_leaves_the_tick_alone() {
+    let (mut ecs, [a, _], since) = world();
+    let mut query = Query::<&mut Position>::new(&mut ecs);
+    query.get_mut(a).unwrap().bypass_change_detection().x = 5;
+    assert!(changed_since::<Position>(&ecs, since).is_empty());
+    assert_eq!(ecs.query::<Position>(a).unwrap().x, 5);
+}
+
+#[test]
+fn writes_outside_queries_count_as_changes() {
+    let (mut ecs, [a, b], since) = world();
+    ecs.query_mut::<Position>(a).unwrap().x = 1;
+    ecs.entity_mut(b).unwrap().get_mut::<Position>().unwrap().y = 1;
+    let mut changed = changed_since::<Position>(&ecs, since);
+    changed.sort();
+
+    let mut expected = vec![a, b];
+    expected.sort();
+    assert_eq!(changed, expected);
+}
+
+#[test]
+fn reading_through_query_mut_is_not_a_change() {
+    let (mut ecs, [a, _], since) = world();
+    assert_eq!(ecs.query_mut::<Position>(a).unwrap().x, 0);
+    assert!(changed_since::<Position>(&ecs, since).is_empty());
+}
+
+#[test]
+fn reflected_writes_count_as_changes() {
+    let (mut ecs, [a, _], since) = world();
+    ecs.reflect_component_mut(a, "Position")
+        .unwrap()
+        .set_path("x", 7i32)
+        .unwrap();
+    assert_eq!(changed_since::<Position>(&ecs, since), [a]);
+}
+
+#[test]
+fn replacing_a_component_is_a_change_not_an_addition() {
+    let (mut ecs, [a, _], since) = world();
+    ecs.add_component(a, Position::new(3, 3)).unwrap();
+    let added = Query::<&Position, Added<Position>>::read_only_since(&ecs, since);
+    assert!(added.iter().next().is_none());
+    assert_eq!(changed_since::<Position>(&ecs, since), [a]);
+}
+
+#[test]
+fn attaching_a_child_changes_the_parents_children() {
+    let (mut ecs, [a, b], _) = world();
+    let c = ecs.spawn().with(Size::new(1)).id();
+    ecs.set_parent(b, a).unwrap();
+    let since = ecs.store.increment_change_tick();
+    ecs.store.increment_change_tick();
+    ecs.set_parent(c, a).unwrap();
+    assert_eq!(changed_since::<Children>(&ecs, since), [a]);
+}
@@ -0,0 +1,82 @@
+use oxygin::{
+    components::{Position, Size},
+    ecs::{Commands, EcsError, IntoSystemConfig, Query, ResMut, Schedule, Stage, ECS},
+};
+
+#[derive(Default)]
+struct Counts(Vec<usize>);
+
+// Every sized entity gets a position and a new sized entity next to it
+fn grow(mut commands: Commands, sized: Query<&Size>) {
+    for (entity, size) in sized.iter() {
+        commands.entity(entity).insert(Position::new(1, 1));
+        commands.spawn((Size::new(size.size + 1),));
+    }
+}
+
+fn count(sized: Query<&Size>, mut counts: ResMut<Counts>) {
+    counts.0.push(sized.iter().count());
+}
+
+#[test]
+fn commands_apply_at_the_end_of_the_stage() {
+    let mut ecs = ECS::new();
+    ecs.init_resource::<Counts>();
+    let entity = ecs.spawn().with(Size::new(1)).id();
+    let mut schedule = Schedule::new();
+    schedule.add_system(Stage::Update, &mut ecs, grow);
+    schedule.add_system(Stage::Update, &mut ecs, count.after(grow));
+    schedule.add_system(Stage::PostUpdate, &mut ecs, count);
+    schedule.run_stage(Stage::Update, &mut ecs).unwrap();
+    schedule.run_stage(Stage::PostUpdate, &mut ecs).unwrap();
+
+    // Nothing changed while `Update` was still running
+    assert_eq!(ecs.get_resource::<Counts>().unwrap().0, [1, 2]);
+    assert_eq!(ecs.query::<Position>(entity).unwrap().x, 1);
+}
+
+#[test]
+fn custom_commands_get_the_whole_world() {
+    let mut ecs = ECS::new();
+    let mut schedule = Schedule::new();
+    schedule.add_system(Stage::Update, &mut ecs, |mut commands: Commands| {
+        commands.add(|ecs| {
+            ecs.add_resource(7u32);
+            Ok(())
+        });
+    });
+    schedule.run_stage(Stage::Update, &mut ecs).unwrap();
+    assert_eq!(*ecs.get_resource::<u32>().unwrap(), 7);
+}
+
+#[test]
+fn failed_commands_are_kept_and_the_rest_still_apply() {
+    let mut ecs = ECS::new();
+    let entity = ecs.spawn().with(Size::new(1)).id();
+    let mut schedule = Schedule::new();
+    schedule.add_system(Stage::Update, &mut ecs, move |mut commands: Commands| {
+        commands.remove::<Position>(entity);
+        commands.insert(entity, Position::new(2, 2));
+        commands.despawn(entity);
+        commands.despawn(entity);
+    });
+    schedule.add_system(Stage::PostUpdate, &mut ecs, |mut commands: Commands| {
+        commands.add(|ecs| {
+            ecs.add_resource(7u32);
+            Ok(())
+        });
+    });
+    schedule.run_stage(Stage::Update, &mut ecs).unwrap();
+    schedule.run_stage(Stage::PostUpdate, &mut ecs).unwrap();
+
+    assert!(matches!(
+        ecs.take_command_errors()[..],
+        [
+            EcsError::UnregisteredComponent(_),
+            EcsError::DeadEntity(dead),
+        ] if dead == entity
+    ));
+    assert!(!ecs.store.contains(entity));
+    assert_eq!(*ecs.get_resource::<u32>().unwrap(), 7);
+    assert!(ecs.take_command_errors().is_empty());
+}
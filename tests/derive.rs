@@ -0,0 +1,68 @@
+use oxygin::ecs::{Component, Reflect, RequiredComponents, StorageKind, ECS};
+
+#[derive(Default, Reflect, Component)]
+#[component(storage = "sparse")]
+struct Sparse;
+
+#[derive(Default, Reflect, Component)]
+#[component(storage = "dense")]
+struct Dense;
+
+#[derive(Default, Reflect, Component)]
+struct Unmarked(u32);
+
+#[derive(Reflect, Component)]
+#[component(storage = "sparse", require(Dense, Unmarked))]
+struct Both;
+
+// Attributes can be split up, constructors run instead of `Default`
+#[derive(Reflect, Component)]
+#[component(require(Sparse))]
+#[component(require(Unmarked = Unmarked(4)))]
+struct Split;
+
+fn required<T: Component>() -> RequiredComponents {
+    let mut required = RequiredComponents::default();
+    T::required_components(&mut required);
+    required
+}
+
+#[test]
+fn storage_defaults_to_dense() {
+    assert_eq!(Sparse::STORAGE, StorageKind::Sparse);
+    assert_eq!(Dense::STORAGE, StorageKind::Dense);
+    assert_eq!(Unmarked::STORAGE, StorageKind::Dense);
+    assert_eq!(Both::STORAGE, StorageKind::Sparse);
+}
+
+#[test]
+fn require_lists_every_component() {
+    let both = required::<Both>();
+    assert_eq!(both.len(), 2);
+    assert!(both.contains::<Dense>() && both.contains::<Unmarked>());
+    let split = required::<Split>();
+    assert_eq!(split.len(), 2);
+    assert!(split.contains::<Sparse>() && split.contains::<Unmarked>());
+    assert!(required::<Unmarked>().is_empty());
+}
+
+#[test]
+fn required_components_are_built_from_default_or_the_expression() {
+    let mut ecs = ECS::new();
+    ecs.register_component::<Both>();
+    ecs.register_component::<Split>();
+    let both = ecs.create_entity();
+    ecs.add_component(both, Both).unwrap();
+    let split = ecs.create_entity();
+    ecs.add_component(split, Split).unwrap();
+    assert_eq!(ecs.query::<Unmarked>(both).unwrap().0, 0);
+    assert!(ecs.query::<Dense>(both).is_ok());
+    assert_eq!(ecs.query::<Unmarked>(split).unwrap().0, 4);
+    assert!(ecs.query::<Sparse>(split).is_ok());
+}
+
+#[test]
+fn bad_attributes_fail_to_compile() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,108 @@
+use std::any::{type_name, TypeId};
+
+use oxygin::{
+    components::{InputState, Position, Size},
+    ecs::{Commands, EcsError, Query, Schedule, Stage, ECS},
+    render::asset_manager::{AssetManager, Model, Square},
+};
+
+#[test]
+fn despawn_drops_every_component() {
+    let mut ecs = ECS::new();
+    let entity = ecs
+        .spawn()
+        .with(Position::new(1, 1))
+        .with(InputState::new())
+        .id();
+    let other = ecs.spawn().with(Position::new(2, 2)).id();
+    ecs.despawn(entity).unwrap();
+
+    assert_eq!(ecs.get_component::<Position>().unwrap().len(), 1);
+    assert!(ecs.get_component::<InputState>().unwrap().is_empty());
+    assert_eq!(ecs.query::<Position>(other).unwrap().x, 2);
+}
+
+#[test]
+fn dead_entities_are_errors() {
+    let mut ecs = ECS::new();
+    let entity = ecs.spawn().with(Size::new(1)).id();
+    ecs.despawn(entity).unwrap();
+
+    assert_eq!(ecs.despawn(entity), Err(EcsError::DeadEntity(entity)));
+    assert!(matches!(
+        ecs.query::<Size>(entity),
+        Err(EcsError::DeadEntity(_))
+    ));
+    assert!(matches!(
+        ecs.add_component(entity, Size::new(2)),
+        Err(EcsError::DeadEntity(_))
+    ));
+}
+
+#[test]
+fn remove_component_hands_the_value_back() {
+    let mut ecs = ECS::new();
+    let entity = ecs.spawn().with(Size::new(4)).id();
+    assert_eq!(ecs.remove_component::<Size>(entity).unwrap().size, 4);
+    assert!(matches!(
+        ecs.remove_component::<Size>(entity),
+        Err(EcsError::MissingComponent { .. })
+    ));
+    assert!(ecs.store.contains(entity));
+}
+
+#[test]
+fn commands_remove_and_despawn() {
+    let mut ecs = ECS::new();
+    let sized = ecs
+        .spawn()
+        .with(Size::new(1))
+        .with(Position::new(0, 0))
+        .id();
+    let positioned = ecs.spawn().with(Position::new(0, 0)).id();
+    let mut schedule = Schedule::new();
+    schedule.add_system(
+        Stage::Update,
+        &mut ecs,
+        move |mut commands: Commands, sized: Query<&Size>| {
+            for (entity, _) in sized.iter() {
+                commands.remove::<Size>(entity);
+            }
+            commands.despawn(positioned);
+        },
+    );
+    schedule.run_stage(Stage::Update, &mut ecs).unwrap();
+
+    assert!(ecs.query::<Size>(sized).is_err());
+    assert!(ecs.query::<Position>(sized).is_ok());
+    assert!(!ecs.store.contains(positioned));
+}
+
+#[test]
+fn removing_an_instance_keeps_the_rest_indexed() {
+    let mut ecs = ECS::new();
+    let [a, b, c] = [(); 3].map(|_| ecs.create_entity());
+    let mut assets = AssetManager::new();
+    let id = TypeId::of::<Square>();
+    assets.register(Model::from(Square::new()), id, type_name::<Square>());
+    for entity in [a, b, c] {
+        assets.add_asset::<Square>(entity);
+    }
+    assets.remove_entity(a);
+
+    let instances = &assets.instances[&id];
+    assert_eq!(instances.entity, [c, b]);
+    assert_eq!(instances.index[&c], 0);
+    assert_eq!(instances.index[&b], 1);
+    assert!(!instances.index.contains_key(&a));
+}
+
+#[test]
+fn registering_twice_keeps_the_column() {
+    let mut ecs = ECS::new();
+    let entity = ecs.spawn().with(Size::new(3)).id();
+    ecs.register_component::<Size>();
+    assert_eq!(ecs.query::<Size>(entity).unwrap().size, 3);
+    ecs.despawn(entity).unwrap();
+    assert!(ecs.get_component::<Size>().unwrap().is_empty());
+}
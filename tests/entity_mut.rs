@@ -0,0 +1,71 @@
+use oxygin::{
+    components::{Edible, Position, Size, Velocity},
+    ecs::{EcsError, Lifecycle, ECS},
+};
+
+#[test]
+fn builds_an_entity_a_call_at_a_time() {
+    let mut ecs = ECS::new();
+    let entity = ecs
+        .spawn()
+        .with(Position::new(1, 2))
+        .with(Size::new(3))
+        .id();
+    assert_eq!(ecs.query::<Size>(entity).unwrap().size, 3);
+
+    ecs.entity_mut(entity)
+        .unwrap()
+        .insert(Velocity::new(1, 1))
+        .remove::<Size>()
+        .remove::<Edible>();
+    assert!(ecs.query::<Size>(entity).is_err());
+    assert!(ecs.query::<Velocity>(entity).is_ok());
+}
+
+#[test]
+fn reads_writes_and_takes_components() {
+    let mut ecs = ECS::new();
+    let entity = ecs.spawn().with(Position::new(1, 2)).id();
+    let mut entity_mut = ecs.entity_mut(entity).unwrap();
+    assert!(entity_mut.contains::<Position>());
+    assert!(!entity_mut.contains::<Size>());
+    entity_mut.get_mut::<Position>().unwrap().x = 9;
+    assert_eq!(entity_mut.get::<Position>().unwrap().x, 9);
+    assert_eq!(entity_mut.take::<Position>().unwrap().x, 9);
+    assert!(entity_mut.take::<Position>().is_none());
+}
+
+#[test]
+fn despawned_entities_have_no_handle() {
+    let mut ecs = ECS::new();
+    let entity = ecs.spawn().with(Size::new(1)).id();
+    ecs.entity_mut(entity).unwrap().despawn();
+    assert!(matches!(
+        ecs.entity_mut(entity),
+        Err(EcsError::DeadEntity(_))
+    ));
+}
+
+#[test]
+fn calls_after_a_hook_despawns_the_entity_do_nothing() {
+    let mut ecs = ECS::new();
+    ecs.add_observer::<Edible>(Lifecycle::Add, |ecs, entity| {
+        ecs.despawn(entity).unwrap();
+    });
+    let mut entity_mut = ecs.spawn();
+    entity_mut.with(Size::new(1)).with(Edible { calories: 1 });
+    assert!(!entity_mut.is_alive());
+    entity_mut
+        .with(Position::new(0, 0))
+        .insert_bundle((Velocity::new(1, 1),))
+        .remove_bundle::<(Size,)>()
+        .observe::<Size>(Lifecycle::Remove, |_, _| {});
+    let entity = entity_mut.id();
+    entity_mut.despawn();
+
+    assert!(!ecs.store.contains(entity));
+    // Never got as far as registering the column
+    assert!(ecs.get_component::<Position>().is_err());
+    // Only the one added above, `observe` didn't attach to the dead entity
+    assert_eq!(ecs.observers.len(), 1);
+}
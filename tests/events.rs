@@ -0,0 +1,78 @@
+use oxygin::ecs::{
+    EventReader, EventWriter, Events, IntoSystemConfig, ResMut, Schedule, Stage, ECS,
+};
+
+struct Ping(u32);
+
+#[derive(Default)]
+struct Seen(Vec<u32>);
+
+#[test]
+fn events_last_two_updates() {
+    let mut events = Events::new();
+    events.send(Ping(1));
+    events.update();
+    events.send(Ping(2));
+    assert_eq!(events.len(), 2);
+    assert_eq!(
+        events.read_from(0).map(|ping| ping.0).collect::<Vec<_>>(),
+        [1, 2]
+    );
+
+    events.update();
+    assert_eq!(
+        events.read_from(0).map(|ping| ping.0).collect::<Vec<_>>(),
+        [2]
+    );
+    events.update();
+    assert!(events.is_empty());
+    assert_eq!(events.event_count(), 2);
+}
+
+fn send(mut writer: EventWriter<Ping>) {
+    writer.send(Ping(1));
+}
+
+fn read(mut reader: EventReader<Ping>, mut seen: ResMut<Seen>) {
+    for ping in reader.read() {
+        seen.0.push(ping.0);
+    }
+}
+
+#[test]
+fn every_reader_sees_each_event_once() {
+    let mut ecs = ECS::new();
+    ecs.add_resource(Events::<Ping>::new());
+    ecs.init_resource::<Seen>();
+    let mut schedule = Schedule::new();
+    schedule.add_system(Stage::PreUpdate, &mut ecs, Events::<Ping>::update_system);
+    schedule.add_system(Stage::Update, &mut ecs, read);
+    schedule.add_system(Stage::Update, &mut ecs, send.before(read));
+    // Runs a stage behind the sender, still sees everything exactly once
+    schedule.add_system(Stage::PostUpdate, &mut ecs, read);
+    for _ in 0..3 {
+        schedule.run_stage(Stage::PreUpdate, &mut ecs).unwrap();
+        schedule.run_stage(Stage::Update, &mut ecs).unwrap();
+        schedule.run_stage(Stage::PostUpdate, &mut ecs).unwrap();
+    }
+    assert_eq!(ecs.get_resource::<Seen>().unwrap().0.len(), 6);
+    assert_eq!(ecs.get_resource::<Events<Ping>>().unwrap().len(), 2);
+}
+
+#[test]
+fn readers_that_fall_behind_miss_expired_events() {
+    let mut ecs = ECS::new();
+    ecs.add_resource(Events::<Ping>::new());
+    ecs.init_resource::<Seen>();
+    let mut schedule = Schedule::new();
+    schedule.add_system(Stage::Update, &mut ecs, read);
+    schedule.run_stage(Stage::Update, &mut ecs).unwrap();
+
+    let events = ecs.get_mut_resource::<Events<Ping>>().unwrap();
+    events.send(Ping(1));
+    events.update();
+    events.send(Ping(2));
+    events.update();
+    schedule.run_stage(Stage::Update, &mut ecs).unwrap();
+    assert_eq!(ecs.get_resource::<Seen>().unwrap().0, [2]);
+}
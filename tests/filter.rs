@@ -0,0 +1,73 @@
+use oxygin::{
+    components::{Edible, InputState, Position},
+    ecs::{Added, Changed, Entity, Or, Query, QueryFilter, With, Without, ECS},
+};
+
+// A player that is somehow also edible, a piece of food and a bare position
+fn world() -> (ECS<'static>, [Entity; 3]) {
+    let mut ecs = ECS::new();
+    let player = ecs
+        .spawn()
+        .with(Position::new(0, 0))
+        .with(InputState::new())
+        .with(Edible { calories: 1 })
+        .id();
+    let food = ecs
+        .spawn()
+        .with(Position::new(0, 0))
+        .with(Edible { calories: 1 })
+        .id();
+    let other = ecs.spawn().with(Position::new(0, 0)).id();
+    (ecs, [player, food, other])
+}
+
+fn matching<F: QueryFilter>(ecs: &ECS) -> Vec<Entity> {
+    let mut entities: Vec<_> = Query::<&Position, F>::read_only(ecs)
+        .iter()
+        .map(|(entity, _)| entity)
+        .collect();
+    entities.sort();
+    entities
+}
+
+#[test]
+fn with_and_without() {
+    let (ecs, [player, food, _]) = world();
+    assert_eq!(
+        matching::<(With<Edible>, Without<InputState>)>(&ecs),
+        vec![food]
+    );
+    assert_eq!(matching::<With<InputState>>(&ecs), vec![player]);
+}
+
+#[test]
+fn or_matches_either() {
+    let (ecs, [player, food, _]) = world();
+    let mut expected = vec![player, food];
+    expected.sort();
+    assert_eq!(
+        matching::<Or<(With<Edible>, With<InputState>)>>(&ecs),
+        expected
+    );
+}
+
+#[test]
+fn added_only_until_trackers_clear() {
+    let (mut ecs, _) = world();
+    assert_eq!(matching::<Added<Position>>(&ecs).len(), 3);
+    ecs.clear_trackers();
+    assert!(matching::<Added<Position>>(&ecs).is_empty());
+}
+
+#[test]
+fn changed_only_sees_written_components() {
+    let (mut ecs, [player, _, _]) = world();
+    ecs.clear_trackers();
+    let mut query = Query::<&mut Position, With<InputState>>::new(&mut ecs);
+    for (_, mut position) in query.iter_mut() {
+        position.x = 3;
+    }
+    assert_eq!(matching::<Changed<Position>>(&ecs), vec![player]);
+    ecs.clear_trackers();
+    assert!(matching::<Changed<Position>>(&ecs).is_empty());
+}
@@ -0,0 +1,117 @@
+use oxygin::{
+    components::{GlobalTransform, Render},
+    ecs::{Children, EcsError, Entity, Parent, ECS},
+    systems::propagate_transforms,
+};
+
+// root -> mid -> leaf, plus an unattached entity
+fn tree() -> (ECS<'static>, [Entity; 4]) {
+    let mut ecs = ECS::new();
+    let root = ecs.spawn().with(Render::new(1.0, 0.0, 0.0, 2.0)).id();
+    let mid = ecs.create_entity();
+    let leaf = ecs.spawn().with(Render::new(1.0, 1.0, 0.0, 1.0)).id();
+    let other = ecs.spawn().with(Render::new(5.0, 0.0, 0.0, 1.0)).id();
+    ecs.set_parent(mid, root).unwrap();
+    ecs.set_parent(leaf, mid).unwrap();
+    (ecs, [root, mid, leaf, other])
+}
+
+#[test]
+fn parent_and_children_agree() {
+    let (ecs, [root, mid, leaf, _]) = tree();
+    assert_eq!(ecs.query::<Parent>(leaf).unwrap().get(), mid);
+    assert_eq!(
+        ecs.query::<Children>(root)
+            .unwrap()
+            .iter()
+            .collect::<Vec<_>>(),
+        [mid]
+    );
+    assert!(ecs.query::<Parent>(root).is_err());
+    assert!(ecs.is_ancestor(root, leaf));
+    assert!(!ecs.is_ancestor(leaf, root));
+}
+
+#[test]
+fn attaching_to_a_descendant_is_a_cycle() {
+    let (mut ecs, [root, _, leaf, _]) = tree();
+    assert_eq!(
+        ecs.set_parent(root, leaf),
+        Err(EcsError::HierarchyCycle {
+            child: root,
+            parent: leaf
+        })
+    );
+}
+
+#[test]
+fn reparenting_moves_the_child() {
+    let (mut ecs, [_, mid, leaf, other]) = tree();
+    ecs.set_parent(leaf, other).unwrap();
+    assert!(ecs.query::<Children>(mid).is_err());
+    assert_eq!(ecs.query::<Children>(other).unwrap().len(), 1);
+    ecs.remove_parent(leaf).unwrap();
+    assert!(ecs.query::<Parent>(leaf).is_err());
+    assert!(ecs.query::<Children>(other).is_err());
+}
+
+#[test]
+fn transforms_apply_down_the_tree() {
+    let (mut ecs, [root, mid, leaf, _]) = tree();
+    propagate_transforms(&mut ecs);
+    let global = ecs.query::<GlobalTransform>(leaf).unwrap().matrix;
+    // The root's scale of 2 applies to the leaf's offset, the entity without a `Render`
+    // in between passes its parent's transform straight through
+    assert_eq!(
+        (global[(0, 3)], global[(1, 3)], global[(0, 0)]),
+        (3.0, 2.0, 2.0)
+    );
+    assert!(ecs.query::<GlobalTransform>(mid).is_err());
+
+    ecs.query_mut::<Render>(root).unwrap().transform.x = 0.0;
+    propagate_transforms(&mut ecs);
+    assert_eq!(
+        ecs.query::<GlobalTransform>(leaf).unwrap().matrix[(0, 3)],
+        2.0
+    );
+}
+
+#[test]
+fn unchanged_transforms_are_not_rewritten() {
+    let (mut ecs, [_, _, leaf, _]) = tree();
+    propagate_transforms(&mut ecs);
+    let ticks = |ecs: &ECS| {
+        ecs.store
+            .get::<GlobalTransform>()
+            .unwrap()
+            .get_ticks(leaf)
+            .unwrap()
+            .changed
+    };
+    let before = ticks(&ecs);
+    ecs.store.increment_change_tick();
+    propagate_transforms(&mut ecs);
+    assert_eq!(ticks(&ecs), before);
+}
+
+#[test]
+fn despawning_a_parent_leaves_its_children_as_roots() {
+    let (mut ecs, [root, mid, leaf, _]) = tree();
+    ecs.despawn(mid).unwrap();
+    assert!(ecs.store.contains(leaf));
+    assert!(ecs.query::<Parent>(leaf).is_err());
+    assert!(ecs.query::<Children>(root).is_err());
+}
+
+#[test]
+fn despawn_recursive_takes_the_subtree() {
+    let (mut ecs, [root, mid, leaf, other]) = tree();
+    ecs.despawn_recursive(root).unwrap();
+    assert!([root, mid, leaf]
+        .iter()
+        .all(|entity| !ecs.store.contains(*entity)));
+    assert!(ecs.store.contains(other));
+    assert_eq!(ecs.get_component::<Render>().unwrap().len(), 1);
+    assert!(ecs.get_component::<Parent>().unwrap().is_empty());
+    assert!(ecs.get_component::<Children>().unwrap().is_empty());
+}
@@ -0,0 +1,49 @@
+use std::{rc::Rc, thread};
+
+use oxygin::ecs::{NonSend, NonSendMut, ResMut, Schedule, Stage, ECS};
+
+#[test]
+fn systems_reach_non_send_resources() {
+    let mut ecs = ECS::new();
+    ecs.add_non_send_resource(Rc::new(5u32));
+    ecs.add_resource(0u64);
+    let mut schedule = Schedule::new();
+    schedule.add_system(Stage::Update, &mut ecs, |mut value: NonSendMut<Rc<u32>>| {
+        *value = Rc::new(**value + 1);
+    });
+    schedule.add_system(
+        Stage::Update,
+        &mut ecs,
+        |value: NonSend<Rc<u32>>, mut copy: ResMut<u64>| *copy = **value as u64,
+    );
+    schedule.run_stage(Stage::Update, &mut ecs).unwrap();
+    assert_eq!(**ecs.get_non_send_resource::<Rc<u32>>().unwrap(), 6);
+    assert_eq!(*ecs.get_resource::<u64>().unwrap(), 6);
+}
+
+#[test]
+fn non_send_resource_scope_lends_the_resource_out() {
+    let mut ecs = ECS::new();
+    ecs.add_non_send_resource(Rc::new(5u32));
+    let value = ecs
+        .non_send_resource_scope(|ecs, value: &mut Rc<u32>| {
+            assert!(!ecs.contains_non_send_resource::<Rc<u32>>());
+            **value
+        })
+        .unwrap();
+    assert_eq!(value, 5);
+    assert!(ecs.remove_non_send_resource::<Rc<u32>>().is_some());
+}
+
+#[test]
+fn other_threads_cant_touch_non_send_resources() {
+    let mut ecs = ECS::new();
+    ecs.add_non_send_resource(Rc::new(5u32));
+    let ecs = &ecs;
+    let result = thread::scope(|scope| {
+        scope
+            .spawn(|| ecs.get_non_send_resource::<Rc<u32>>().is_some())
+            .join()
+    });
+    assert!(result.is_err());
+}
@@ -0,0 +1,146 @@
+use std::{
+    any::{type_name, TypeId},
+    sync::{Arc, Mutex},
+};
+
+use oxygin::{
+    components::{GlobalTransform, Position, Render, Velocity},
+    ecs::{Children, Entity, Lifecycle, Parent, ECS},
+    render::asset_manager::{add_render_hooks, AssetManager, Food, Model, Square},
+    systems::propagate_transforms,
+};
+
+#[derive(Default)]
+struct Log(Vec<&'static str>);
+
+fn log(ecs: &mut ECS, entry: &'static str) {
+    ecs.get_mut_resource::<Log>().unwrap().0.push(entry);
+}
+
+fn logged(ecs: &ECS) -> Vec<&'static str> {
+    ecs.get_resource::<Log>().unwrap().0.clone()
+}
+
+#[test]
+fn hooks_run_for_each_lifecycle() {
+    let mut ecs = ECS::new();
+    ecs.init_resource::<Log>();
+    ecs.register_component::<Position>();
+    ecs.store.on_add::<Position>(|ecs, _| log(ecs, "add"));
+    ecs.store.on_insert::<Position>(|ecs, _| log(ecs, "insert"));
+    ecs.store.on_remove::<Position>(|ecs, _| log(ecs, "remove"));
+
+    let entity = ecs.spawn().with(Position::new(0, 0)).id();
+    ecs.add_component(entity, Position::new(1, 1)).unwrap();
+    ecs.remove_component::<Position>(entity).unwrap();
+    ecs.add_component(entity, Position::new(2, 2)).unwrap();
+    ecs.despawn(entity).unwrap();
+    assert_eq!(
+        logged(&ecs),
+        ["add", "insert", "insert", "remove", "add", "insert", "remove"]
+    );
+}
+
+#[test]
+fn observers_can_change_the_world() {
+    let mut ecs = ECS::new();
+    ecs.register_component::<Position>();
+    ecs.add_observer::<Velocity>(Lifecycle::Add, |ecs, entity| {
+        ecs.add_component(entity, Position::new(9, 9)).unwrap();
+    });
+    let entity = ecs.spawn().with(Velocity::new(1, 1)).id();
+    assert_eq!(ecs.query::<Position>(entity).unwrap().x, 9);
+}
+
+#[test]
+fn entity_observers_go_away_with_the_entity() {
+    let mut ecs = ECS::new();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let watched = ecs.spawn().with(Position::new(0, 0)).id();
+    let other = ecs.spawn().with(Position::new(0, 0)).id();
+    let log = seen.clone();
+    ecs.observe::<Position>(watched, Lifecycle::Remove, move |_, entity| {
+        log.lock().unwrap().push(entity)
+    })
+    .unwrap();
+
+    ecs.despawn(other).unwrap();
+    ecs.despawn(watched).unwrap();
+    assert_eq!(*seen.lock().unwrap(), [watched]);
+    assert!(ecs.observers.is_empty());
+}
+
+#[test]
+fn detaching_runs_parent_and_children_remove_hooks() {
+    let mut ecs = ECS::new();
+    let removed: Arc<Mutex<Vec<(&str, Entity)>>> = Arc::default();
+    let log = removed.clone();
+    ecs.add_observer::<Parent>(Lifecycle::Remove, move |_, entity| {
+        log.lock().unwrap().push(("parent", entity))
+    });
+    let log = removed.clone();
+    ecs.add_observer::<Children>(Lifecycle::Remove, move |_, entity| {
+        log.lock().unwrap().push(("children", entity))
+    });
+    let root = ecs.create_entity();
+    let child = ecs.create_entity();
+    let grandchild = ecs.create_entity();
+    ecs.set_parent(child, root).unwrap();
+    ecs.set_parent(grandchild, child).unwrap();
+
+    ecs.remove_parent(child).unwrap();
+    assert_eq!(
+        removed.lock().unwrap().drain(..).collect::<Vec<_>>(),
+        [("parent", child), ("children", root)]
+    );
+    ecs.despawn(child).unwrap();
+    assert_eq!(
+        removed.lock().unwrap().drain(..).collect::<Vec<_>>(),
+        [("children", child), ("parent", grandchild)]
+    );
+}
+
+// What the renderer would draw the entity with for `model`, if anything
+fn drawn<M: 'static>(ecs: &ECS, entity: Entity) -> Option<[[f32; 4]; 4]> {
+    let instances = &ecs.get_resource::<AssetManager>().unwrap().instances[&TypeId::of::<M>()];
+    let index = *instances.index.get(&entity)?;
+    Some(instances.instances[index].model)
+}
+
+#[test]
+fn reinserting_a_render_keeps_drawing_it_in_place() {
+    let mut ecs = ECS::new();
+    let mut assets = AssetManager::new();
+    assets.register(
+        Model::from(Square::new()),
+        TypeId::of::<Square>(),
+        type_name::<Square>(),
+    );
+    assets.register(
+        Model::from(Food::new()),
+        TypeId::of::<Food>(),
+        type_name::<Food>(),
+    );
+    ecs.add_resource(assets);
+    add_render_hooks(&mut ecs);
+    let render = Render::new(0.5, 0.5, 0.0, 0.2);
+    let entity = ecs.spawn().with(render.clone().with_model::<Square>()).id();
+    propagate_transforms(&mut ecs);
+    let matrix = ecs.query::<GlobalTransform>(entity).unwrap().to_raw();
+    // Standing in for the renderer picking up the changed transform
+    ecs.get_mut_resource::<AssetManager>()
+        .unwrap()
+        .update_instance(entity, matrix);
+
+    // Nothing moves, so the renderer has nothing new to upload
+    ecs.add_component(entity, render.clone().with_model::<Square>())
+        .unwrap();
+    propagate_transforms(&mut ecs);
+    assert_eq!(drawn::<Square>(&ecs, entity), Some(matrix.model));
+
+    ecs.add_component(entity, render.with_model::<Food>())
+        .unwrap();
+    propagate_transforms(&mut ecs);
+    assert_eq!(drawn::<Square>(&ecs, entity), None);
+    assert_eq!(drawn::<Food>(&ecs, entity), Some(matrix.model));
+}
@@ -0,0 +1,113 @@
+use oxygin::{
+    components::{Position, Size, Velocity},
+    ecs::{Entity, Query, QueryError, ECS},
+};
+
+// Two moving entities, one of them sized, and one that stands still
+fn world() -> (ECS<'static>, [Entity; 3]) {
+    let mut ecs = ECS::new();
+    let moving = ecs
+        .spawn()
+        .with(Position::new(0, 0))
+        .with(Velocity::new(1, 2))
+        .id();
+    let sized = ecs
+        .spawn()
+        .with(Position::new(5, 5))
+        .with(Velocity::new(1, 1))
+        .with(Size::new(3))
+        .id();
+    let still = ecs.spawn().with(Position::new(5, 5)).id();
+    (ecs, [moving, sized, still])
+}
+
+#[test]
+fn iterates_entities_with_every_component() {
+    let (mut ecs, [moving, sized, still]) = world();
+    let mut query = Query::<(&mut Position, &Velocity)>::new(&mut ecs);
+    let mut seen = Vec::new();
+    for (entity, (mut position, velocity)) in query.iter_mut() {
+        position.x += velocity.vx;
+        seen.push(entity);
+    }
+    seen.sort();
+
+    let mut expected = vec![moving, sized];
+    expected.sort();
+    assert_eq!(seen, expected);
+    assert_eq!(ecs.query::<Position>(moving).unwrap().x, 1);
+    assert_eq!(ecs.query::<Position>(still).unwrap().x, 5);
+}
+
+#[test]
+fn optional_components_do_not_filter() {
+    let (ecs, [moving, sized, _]) = world();
+    let query = Query::<(&Velocity, Option<&Size>)>::read_only(&ecs);
+    assert_eq!(query.iter().count(), 2);
+    assert!(query.get(moving).unwrap().1.is_none());
+    assert_eq!(query.get(sized).unwrap().1.unwrap().size, 3);
+}
+
+#[test]
+fn get_skips_entities_missing_a_component() {
+    let (mut ecs, [moving, _, still]) = world();
+    let mut query = Query::<(&mut Position, &Velocity)>::new(&mut ecs);
+    assert!(query.get(still).is_none());
+    assert!(query.get_mut(still).is_none());
+    query.get_mut(moving).unwrap().0.y = 9;
+    assert_eq!(query.get(moving).unwrap().0.y, 9);
+}
+
+#[test]
+#[should_panic(expected = "conflicts with a &mut")]
+fn shared_and_mutable_borrow_of_one_component_panics() {
+    let mut ecs = ECS::new();
+    let _query = Query::<(&mut Position, &Position)>::new(&mut ecs);
+}
+
+#[test]
+#[should_panic(expected = "conflicts with another borrow")]
+fn two_mutable_borrows_of_one_component_panic() {
+    let mut ecs = ECS::new();
+    let _query = Query::<(&mut Position, &mut Position)>::new(&mut ecs);
+}
+
+#[test]
+fn single_needs_exactly_one_match() {
+    let (mut ecs, [_, sized, _]) = world();
+    let mut query = Query::<&mut Size>::new(&mut ecs);
+    query.single_mut().unwrap().1.size = 5;
+    assert_eq!(query.single().unwrap().0, sized);
+    assert_eq!(ecs.query::<Size>(sized).unwrap().size, 5);
+
+    let query = Query::<&Velocity>::read_only(&ecs);
+    assert!(matches!(
+        query.single(),
+        Err(QueryError::MultipleEntities(_))
+    ));
+    let empty = ECS::new();
+    assert!(matches!(
+        Query::<&Size>::read_only(&empty).single(),
+        Err(QueryError::NoEntities(_))
+    ));
+}
+
+#[test]
+fn get_many_mut_hands_out_disjoint_items() {
+    let (mut ecs, [moving, sized, still]) = world();
+    let mut query = Query::<&mut Position>::new(&mut ecs);
+    let [mut first, mut second] = query.get_many_mut([moving, sized]).unwrap();
+    std::mem::swap(&mut first.x, &mut second.x);
+    assert_eq!(query.get(moving).unwrap().x, 5);
+    assert_eq!(query.get(sized).unwrap().x, 0);
+    assert_eq!(
+        query.get_many_mut([still, still]).err(),
+        Some(QueryError::AliasedEntity(still))
+    );
+
+    let mut query = Query::<(&mut Position, &Velocity)>::new(&mut ecs);
+    assert_eq!(
+        query.get_many_mut([moving, still]).err(),
+        Some(QueryError::NoMatch(still))
+    );
+}
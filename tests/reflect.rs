@@ -0,0 +1,80 @@
+use std::any::{type_name, TypeId};
+
+use nalgebra::Vector3;
+use oxygin::{
+    components::{Render, Size},
+    ecs::{Component, Entity, Reflect, ReflectError, ECS},
+};
+
+#[derive(Reflect)]
+struct Wrapper<T: Reflect>(T);
+
+impl<T: Reflect> Component for Wrapper<T> {}
+
+fn world() -> (ECS<'static>, Entity) {
+    let mut ecs = ECS::new();
+    ecs.register_component::<Render>();
+    ecs.register_component::<Wrapper<Size>>();
+    let entity = ecs.create_entity();
+    ecs.add_component(entity, Render::new(0.5, 0.0, 0.0, 1.0))
+        .unwrap();
+    ecs.add_component(entity, Wrapper(Size::new(3))).unwrap();
+    (ecs, entity)
+}
+
+#[test]
+fn components_are_found_by_short_and_full_name() {
+    let (ecs, _) = world();
+    let registry = ecs.type_registry();
+    let render = registry.get_by_name("Render").unwrap();
+    assert_eq!(render.info.type_name, type_name::<Render>());
+    assert!(registry.get_by_name(type_name::<Render>()).is_some());
+
+    // Only the outer type loses its path
+    let wrapper = registry
+        .get_by_name("Wrapper<oxygin::components::Size>")
+        .unwrap();
+    assert_eq!(wrapper.info.type_id, TypeId::of::<Wrapper<Size>>());
+    assert!(registry.get_by_name("Size>").is_none());
+    assert!(registry.get_by_name("Missing").is_none());
+}
+
+#[test]
+fn nested_fields_are_reached_by_path() {
+    let (mut ecs, entity) = world();
+    let render = ecs.reflect_component_mut(entity, "Render").unwrap();
+    render.set_path("transform.y", 0.25f32).unwrap();
+    let x = render.field("transform").unwrap().field("x").unwrap();
+    assert_eq!(x.as_any().downcast_ref::<f32>(), Some(&0.5));
+    assert_eq!(ecs.query::<Render>(entity).unwrap().transform.y, 0.25);
+
+    let wrapper = ecs
+        .reflect_component(entity, "Wrapper<oxygin::components::Size>")
+        .unwrap();
+    assert_eq!(*wrapper.get_path::<u32>("0.size").unwrap(), 3);
+    let info = Render::type_info();
+    assert_eq!(
+        info.field("scale").unwrap().type_id,
+        TypeId::of::<Vector3<f32>>()
+    );
+}
+
+#[test]
+fn bad_paths_and_wrong_types_are_errors() {
+    let (mut ecs, entity) = world();
+    let render = ecs.reflect_component_mut(entity, "Render").unwrap();
+    assert_eq!(
+        render.set_path("transform.w", 1.0f32),
+        Err(ReflectError::NoField("transform.w".to_string()))
+    );
+    assert_eq!(
+        render.set_path("transform.x", 1u32),
+        Err(ReflectError::WrongType {
+            path: "transform.x".to_string(),
+            expected: "u32",
+        })
+    );
+    assert!(render.get_path::<f64>("scale.x").is_err());
+    assert!(render.path("scale.x.y").is_none());
+    assert_eq!(ecs.query::<Render>(entity).unwrap().transform.x, 0.5);
+}
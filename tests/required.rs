@@ -0,0 +1,69 @@
+use oxygin::{
+    components::{GlobalTransform, Gravity, Position, Render, Velocity},
+    ecs::{Component, Lifecycle, Reflect, StorageKind, ECS},
+};
+
+#[derive(Default, Reflect, Component)]
+#[component(storage = "sparse")]
+struct Tag;
+
+#[derive(Reflect, Component)]
+#[component(require(Tag, Hp = Hp(10)))]
+struct Boss {
+    level: u32,
+}
+
+#[derive(Default, Reflect, Component)]
+struct Hp(u32);
+
+#[test]
+fn storage_comes_from_the_derive() {
+    assert_eq!(Tag::STORAGE, StorageKind::Sparse);
+    assert_eq!(Hp::STORAGE, StorageKind::Dense);
+}
+
+#[test]
+fn requirements_are_filled_from_their_constructors() {
+    let mut ecs = ECS::new();
+    let boss = ecs.spawn().with(Boss { level: 3 }).id();
+    assert_eq!(ecs.query::<Boss>(boss).unwrap().level, 3);
+    assert_eq!(ecs.query::<Hp>(boss).unwrap().0, 10);
+    assert!(ecs.query::<Tag>(boss).is_ok());
+}
+
+#[test]
+fn existing_components_are_kept() {
+    let mut ecs = ECS::new();
+    let boss = ecs.spawn().with(Hp(5)).with(Boss { level: 1 }).id();
+    assert_eq!(ecs.query::<Hp>(boss).unwrap().0, 5);
+}
+
+#[test]
+fn requirements_chain() {
+    let mut ecs = ECS::new();
+    let entity = ecs.spawn().with(Gravity::new(0.0, 1.0)).id();
+    assert_eq!(ecs.query::<Velocity>(entity).unwrap().vx, 0);
+    assert_eq!(ecs.query::<Position>(entity).unwrap().x, 0);
+    let drawn = ecs.spawn().with(Render::new(0.0, 0.0, 0.0, 1.0)).id();
+    assert!(ecs.query::<GlobalTransform>(drawn).is_ok());
+}
+
+#[test]
+fn bundle_fields_win_over_requirements() {
+    let mut ecs = ECS::new();
+    ecs.add_resource(0u32);
+    ecs.add_observer::<Position>(Lifecycle::Add, |ecs, _| {
+        *ecs.get_mut_resource::<u32>().unwrap() += 1;
+    });
+    let entity = ecs
+        .add_bundle((
+            Gravity::new(0.0, 1.0),
+            Velocity::new(3, 3),
+            Position::new(7, 7),
+        ))
+        .unwrap();
+    assert_eq!(ecs.query::<Velocity>(entity).unwrap().vx, 3);
+    assert_eq!(ecs.query::<Position>(entity).unwrap().x, 7);
+    // Added once from the bundle, never by a requirement first
+    assert_eq!(*ecs.get_resource::<u32>().unwrap(), 1);
+}
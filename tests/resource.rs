@@ -0,0 +1,56 @@
+use oxygin::{
+    components::Size,
+    ecs::{EcsError, FromWorld, ECS},
+};
+
+#[derive(Default)]
+struct Count(u32);
+
+struct Doubled(u32);
+
+impl FromWorld for Doubled {
+    fn from_world(ecs: &mut ECS) -> Self {
+        Doubled(ecs.get_resource::<Count>().unwrap().0 * 2)
+    }
+}
+
+#[test]
+fn init_resource_keeps_an_existing_value() {
+    let mut ecs = ECS::new();
+    ecs.init_resource::<Count>();
+    ecs.get_mut_resource::<Count>().unwrap().0 = 4;
+    ecs.init_resource::<Count>();
+    assert_eq!(ecs.get_resource::<Count>().unwrap().0, 4);
+    ecs.init_resource::<Doubled>();
+    assert_eq!(ecs.get_resource::<Doubled>().unwrap().0, 8);
+}
+
+#[test]
+fn resource_scope_lends_the_resource_out() {
+    let mut ecs = ECS::new();
+    ecs.add_resource(Count(4));
+    let entity = ecs.spawn().with(Size::new(1)).id();
+    let returned = ecs
+        .resource_scope(|ecs, count: &mut Count| {
+            assert!(!ecs.contains_resource::<Count>());
+            ecs.query_mut::<Size>(entity).unwrap().size += count.0;
+            count.0 += 1;
+            7
+        })
+        .unwrap();
+    assert_eq!(returned, 7);
+    assert_eq!(ecs.query::<Size>(entity).unwrap().size, 5);
+    assert_eq!(ecs.get_resource::<Count>().unwrap().0, 5);
+}
+
+#[test]
+fn removed_resources_are_missing() {
+    let mut ecs = ECS::new();
+    ecs.add_resource(Count(1));
+    assert_eq!(ecs.remove_resource::<Count>().unwrap().0, 1);
+    assert!(!ecs.contains_resource::<Count>());
+    assert!(matches!(
+        ecs.resource_scope(|_, _: &mut Count| ()),
+        Err(EcsError::MissingResource(_))
+    ));
+}
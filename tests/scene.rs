@@ -0,0 +1,111 @@
+use oxygin::{
+    components::{Gravity, Position, Render, Velocity},
+    ecs::{Component, Lifecycle, Parent, Reflect, Scene, ECS},
+};
+use serde::{Deserialize, Serialize};
+
+mod other {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Reflect, Component)]
+    pub struct Label(pub u32);
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Reflect, Component)]
+struct Tagged<T: Reflect>(T);
+
+fn registered() -> ECS<'static> {
+    let mut ecs = ECS::new();
+    ecs.register_scene_component::<Position>();
+    ecs.register_scene_component::<Render>();
+    ecs.register_scene_component::<Tagged<other::Label>>();
+    ecs.register_scene_component::<Tagged<u32>>();
+    ecs
+}
+
+#[test]
+fn round_trips_through_ron_and_json() {
+    let mut ecs = registered();
+    // Not registered for scenes so it's left out
+    ecs.register_component::<Velocity>();
+    let a = ecs
+        .spawn()
+        .with(Position::new(3, 4))
+        .with(Velocity::new(3, 4))
+        .id();
+    let b = ecs.spawn().with(Render::new(0.1, 0.2, 0.0, 0.5)).id();
+    ecs.set_parent(b, a).unwrap();
+
+    let scene = Scene::from_ecs(&ecs).unwrap();
+    let ron = scene.to_ron().unwrap();
+    let json = scene.to_json().unwrap();
+    assert!(!ron.contains("Velocity"));
+    assert_eq!(Scene::from_ron(&ron).unwrap(), scene);
+    assert_eq!(Scene::from_json(&json).unwrap(), scene);
+
+    let mut loaded = registered();
+    // Taken so the loaded entities can't keep their old keys by accident
+    let _taken = loaded.create_entity();
+    let entities = Scene::from_ron(&ron).unwrap().spawn(&mut loaded).unwrap();
+    let (a, b) = (entities[&0], entities[&1]);
+    assert_eq!(loaded.query::<Position>(a).unwrap().y, 4);
+    assert_eq!(loaded.query::<Render>(b).unwrap().transform.y, 0.2);
+    assert_eq!(loaded.query::<Parent>(b).unwrap().get(), a);
+}
+
+#[test]
+fn unknown_components_fail_to_load() {
+    let mut ecs = registered();
+    ecs.spawn().with(Position::new(0, 0));
+    let json = Scene::from_ecs(&ecs).unwrap().to_json().unwrap();
+    let scene = Scene::from_json(&json.replace("Position", "Missing")).unwrap();
+    assert!(scene.spawn(&mut registered()).is_err());
+}
+
+#[test]
+fn generic_components_keep_their_parameters() {
+    let mut ecs = registered();
+    let entity = ecs
+        .spawn()
+        .with(Tagged(other::Label(1)))
+        .with(Tagged(2u32))
+        .id();
+    let json = Scene::from_ecs(&ecs).unwrap().to_json().unwrap();
+    assert!(json.contains("Tagged<scene::other::Label>"), "{}", json);
+    assert!(json.contains("Tagged<u32>"), "{}", json);
+
+    let mut loaded = registered();
+    let entities = Scene::from_json(&json).unwrap().spawn(&mut loaded).unwrap();
+    let loaded_entity = entities[&0];
+    assert_eq!(
+        loaded.query::<Tagged<other::Label>>(loaded_entity).unwrap(),
+        ecs.query::<Tagged<other::Label>>(entity).unwrap()
+    );
+    assert_eq!(loaded.query::<Tagged<u32>>(loaded_entity).unwrap().0, 2);
+}
+
+#[test]
+fn saved_components_win_over_requirements() {
+    let mut ecs = registered();
+    ecs.register_scene_component::<Gravity>();
+    let entity = ecs
+        .spawn()
+        .with(Position::new(7, 7))
+        .with(Gravity::new(0.0, 1.0))
+        .id();
+    ecs.remove_component::<Velocity>(entity).unwrap();
+    let ron = Scene::from_ecs(&ecs).unwrap().to_ron().unwrap();
+
+    let mut loaded = registered();
+    loaded.register_scene_component::<Gravity>();
+    loaded.add_resource(0u32);
+    loaded.add_observer::<Position>(Lifecycle::Insert, |ecs, _| {
+        *ecs.get_mut_resource::<u32>().unwrap() += 1;
+    });
+    let entities = Scene::from_ron(&ron).unwrap().spawn(&mut loaded).unwrap();
+    assert_eq!(loaded.query::<Position>(entities[&0]).unwrap().x, 7);
+    // Missing from the scene, so filled in
+    assert_eq!(loaded.query::<Velocity>(entities[&0]).unwrap().vx, 0);
+    // Inserted once from the scene, never by a requirement first
+    assert_eq!(*loaded.get_resource::<u32>().unwrap(), 1);
+}
@@ -0,0 +1,158 @@
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+use oxygin::ecs::{IntoSystemConfig, Res, ResMut, Schedule, ScheduleError, Stage, ECS};
+
+#[derive(Default)]
+struct Log(Vec<&'static str>);
+
+fn first(mut log: ResMut<Log>) {
+    log.0.push("first");
+}
+
+fn second(mut log: ResMut<Log>) {
+    log.0.push("second");
+}
+
+fn third(mut log: ResMut<Log>) {
+    log.0.push("third");
+}
+
+fn a() {}
+fn b() {}
+fn c() {}
+
+#[test]
+fn runs_systems_in_dependency_order() {
+    let mut ecs = ECS::new();
+    ecs.init_resource::<Log>();
+    let mut schedule = Schedule::new();
+    schedule.add_system(Stage::Update, &mut ecs, third.after(second));
+    schedule.add_system(Stage::Update, &mut ecs, first.before(second));
+    schedule.add_system(Stage::Update, &mut ecs, second);
+    schedule.run_stage(Stage::Update, &mut ecs).unwrap();
+    assert_eq!(
+        ecs.get_resource::<Log>().unwrap().0,
+        ["first", "second", "third"]
+    );
+}
+
+#[test]
+fn only_the_requested_stage_runs() {
+    let mut ecs = ECS::new();
+    ecs.init_resource::<Log>();
+    let mut schedule = Schedule::new();
+    schedule.add_system(Stage::Update, &mut ecs, first);
+    schedule.add_system(Stage::PostUpdate, &mut ecs, second);
+    schedule.run_stage(Stage::Update, &mut ecs).unwrap();
+    assert_eq!(ecs.get_resource::<Log>().unwrap().0, ["first"]);
+}
+
+#[test]
+fn cycles_are_reported_with_the_systems_in_them() {
+    let mut ecs = ECS::new();
+    let mut schedule = Schedule::new();
+    schedule.add_system(Stage::Update, &mut ecs, a.after(b));
+    schedule.add_system(Stage::Update, &mut ecs, b.after(a));
+    schedule.add_system(Stage::Update, &mut ecs, c.before(a));
+    let Err(ScheduleError::Cycle { stage, systems }) = schedule.build() else {
+        panic!("expected a cycle");
+    };
+    assert_eq!(stage, Stage::Update);
+    assert_eq!(systems.len(), 2);
+    assert!(systems.iter().all(|system| !system.ends_with("::c")));
+}
+
+#[test]
+fn systems_after_a_cycle_are_left_out_of_it() {
+    let mut ecs = ECS::new();
+    let mut schedule = Schedule::new();
+    schedule.add_system(Stage::Update, &mut ecs, a.after(b));
+    schedule.add_system(Stage::Update, &mut ecs, b.after(a));
+    schedule.add_system(Stage::Update, &mut ecs, c.after(a));
+    let Err(ScheduleError::Cycle { systems, .. }) = schedule.build() else {
+        panic!("expected a cycle");
+    };
+    assert_eq!(systems.len(), 2);
+    assert!(systems[0].ends_with("::a"));
+    assert!(systems[1].ends_with("::b"));
+}
+
+#[test]
+fn ordering_against_a_missing_system_fails() {
+    let mut ecs = ECS::new();
+    let mut schedule = Schedule::new();
+    schedule.add_system(Stage::Update, &mut ecs, a.after(b));
+    assert!(matches!(
+        schedule.build(),
+        Err(ScheduleError::UnknownSystem {
+            stage: Stage::Update,
+            ..
+        })
+    ));
+}
+
+// Both have to be running at once for either to see the other arrive
+struct Rendezvous(AtomicUsize);
+struct Left(bool);
+struct Right(bool);
+
+fn meet(rendezvous: &Rendezvous) -> bool {
+    rendezvous.0.fetch_add(1, Ordering::SeqCst);
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while Instant::now() < deadline {
+        if rendezvous.0.load(Ordering::SeqCst) == 2 {
+            return true;
+        }
+        thread::yield_now();
+    }
+    false
+}
+
+fn left(rendezvous: Res<Rendezvous>, mut met: ResMut<Left>) {
+    met.0 = meet(&rendezvous);
+}
+
+fn right(rendezvous: Res<Rendezvous>, mut met: ResMut<Right>) {
+    met.0 = meet(&rendezvous);
+}
+
+#[test]
+fn systems_without_conflicts_run_at_the_same_time() {
+    let mut ecs = ECS::new();
+    ecs.add_resource(Rendezvous(AtomicUsize::new(0)));
+    ecs.add_resource(Left(false));
+    ecs.add_resource(Right(false));
+    let mut schedule = Schedule::new();
+    schedule.add_system(Stage::Update, &mut ecs, left);
+    schedule.add_system(Stage::Update, &mut ecs, right);
+    // The global pool can be a single thread on a small machine
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(2)
+        .build()
+        .unwrap();
+    pool.install(|| schedule.run_stage(Stage::Update, &mut ecs))
+        .unwrap();
+    assert!(ecs.get_resource::<Left>().unwrap().0);
+    assert!(ecs.get_resource::<Right>().unwrap().0);
+}
+
+#[test]
+fn conflicting_and_exclusive_systems_keep_their_order() {
+    let mut ecs = ECS::new();
+    ecs.init_resource::<Log>();
+    let mut schedule = Schedule::new();
+    schedule.add_system(Stage::Update, &mut ecs, first);
+    schedule.add_system(Stage::Update, &mut ecs, |ecs: &mut ECS| {
+        ecs.get_mut_resource::<Log>().unwrap().0.push("exclusive");
+    });
+    schedule.add_system(Stage::Update, &mut ecs, second);
+    schedule.run_stage(Stage::Update, &mut ecs).unwrap();
+    assert_eq!(
+        ecs.get_resource::<Log>().unwrap().0,
+        ["first", "exclusive", "second"]
+    );
+}
@@ -0,0 +1,93 @@
+use oxygin::{
+    components::{InputState, Position},
+    ecs::{Added, Changed, Component, Entity, Query, StorageKind, ECS},
+};
+
+// `InputState` is sparse and `Position` dense, one entity has both
+fn world() -> (ECS<'static>, [Entity; 3]) {
+    let mut ecs = ECS::new();
+    ecs.register_component::<Position>();
+    ecs.register_component::<InputState>();
+    let both = ecs.create_entity();
+    ecs.add_component(both, Position::new(1, 1)).unwrap();
+    ecs.add_component(both, InputState::new()).unwrap();
+    let input = ecs.create_entity();
+    ecs.add_component(input, InputState::new()).unwrap();
+    let positioned = ecs.create_entity();
+    ecs.add_component(positioned, Position::new(2, 2)).unwrap();
+    (ecs, [both, input, positioned])
+}
+
+fn sorted(mut entities: Vec<Entity>) -> Vec<Entity> {
+    entities.sort();
+    entities
+}
+
+#[test]
+fn columns_use_the_components_storage() {
+    let (ecs, _) = world();
+    assert_eq!(InputState::STORAGE, StorageKind::Sparse);
+    let inputs = ecs.get_component::<InputState>().unwrap();
+    assert_eq!(inputs.storage_kind(), StorageKind::Sparse);
+    assert_eq!(
+        ecs.get_component::<Position>().unwrap().storage_kind(),
+        StorageKind::Dense
+    );
+}
+
+#[test]
+fn sparse_columns_insert_iterate_and_remove() {
+    let (mut ecs, [both, input, positioned]) = world();
+    let inputs = ecs.get_component::<InputState>().unwrap();
+    assert_eq!(inputs.len(), 2);
+    assert_eq!(
+        sorted(inputs.iter().map(|(entity, _)| entity).collect()),
+        sorted(vec![both, input])
+    );
+    assert!(!inputs.contains_key(positioned));
+
+    ecs.remove_component::<InputState>(both).unwrap();
+    let inputs = ecs.get_component::<InputState>().unwrap();
+    assert_eq!(inputs.keys().collect::<Vec<_>>(), [input]);
+    assert_eq!(ecs.query::<Position>(both).unwrap().x, 1);
+}
+
+#[test]
+fn queries_mix_sparse_and_dense_columns() {
+    let (mut ecs, [both, _, _]) = world();
+    let mut query = Query::<(&mut InputState, &Position)>::new(&mut ecs);
+    let mut matched = Vec::new();
+    for (entity, (mut input, position)) in query.iter_mut() {
+        input.up = position.x > 0;
+        matched.push(entity);
+    }
+    assert_eq!(matched, [both]);
+    assert!(ecs.query::<InputState>(both).unwrap().up);
+}
+
+#[test]
+fn added_and_changed_work_on_sparse_columns() {
+    let (mut ecs, [both, input, _]) = world();
+    let added = Query::<&Position, Added<InputState>>::read_only(&ecs);
+    assert_eq!(
+        added.iter().map(|(entity, _)| entity).collect::<Vec<_>>(),
+        [both]
+    );
+    ecs.clear_trackers();
+
+    let mut query = Query::<(&mut InputState, &Position)>::new(&mut ecs);
+    for (_, (mut input, _)) in query.iter_mut() {
+        input.left = true;
+    }
+    ecs.add_component(input, Position::new(0, 0)).unwrap();
+    let changed = Query::<&Position, Changed<InputState>>::read_only(&ecs);
+    assert_eq!(
+        changed.iter().map(|(entity, _)| entity).collect::<Vec<_>>(),
+        [both]
+    );
+    let added = Query::<&InputState, Added<Position>>::read_only(&ecs);
+    assert_eq!(
+        added.iter().map(|(entity, _)| entity).collect::<Vec<_>>(),
+        [input]
+    );
+}
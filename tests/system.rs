@@ -0,0 +1,103 @@
+use oxygin::{
+    components::{Position, Size, Velocity},
+    ecs::{Commands, IntoSystem, Query, Res, ResMut, System, ECS},
+};
+
+struct Step(i32);
+
+#[derive(Default)]
+struct Moved(usize);
+
+fn run<M>(ecs: &mut ECS, system: impl IntoSystem<M>) {
+    let mut system = system.into_system();
+    system.initialize(ecs);
+    system.run(ecs);
+}
+
+fn world() -> ECS<'static> {
+    let mut ecs = ECS::new();
+    ecs.add_resource(Step(2));
+    ecs.add_resource(Moved::default());
+    ecs.register_component::<Position>();
+    ecs.register_component::<Velocity>();
+    for vx in [1, 3] {
+        let entity = ecs.create_entity();
+        ecs.add_component(entity, Position::new(0, 0)).unwrap();
+        ecs.add_component(entity, Velocity::new(vx, 0)).unwrap();
+    }
+    let still = ecs.create_entity();
+    ecs.add_component(still, Position::new(0, 0)).unwrap();
+    ecs
+}
+
+fn step(step: Res<Step>, mut moved: ResMut<Moved>, mut query: Query<(&mut Position, &Velocity)>) {
+    for (_, (mut position, velocity)) in query.iter_mut() {
+        position.x += velocity.vx * step.0;
+        moved.0 += 1;
+    }
+}
+
+#[test]
+fn parameters_are_fetched_from_the_world() {
+    let mut ecs = world();
+    run(&mut ecs, step);
+    assert_eq!(ecs.get_resource::<Moved>().unwrap().0, 2);
+    let positions = Query::<&Position>::read_only(&ecs);
+    let mut xs: Vec<i32> = positions.iter().map(|(_, position)| position.x).collect();
+    xs.sort();
+    assert_eq!(xs, [0, 2, 6]);
+}
+
+fn read_twice(a: Query<&Position>, b: Query<&Position>, c: Res<Step>, d: Res<Step>) {
+    assert_eq!(a.iter().count(), b.iter().count());
+    assert_eq!(c.0, d.0);
+}
+
+#[test]
+fn shared_borrows_can_repeat() {
+    run(&mut world(), read_twice);
+}
+
+#[test]
+fn systems_queue_commands_next_to_other_parameters() {
+    let mut ecs = world();
+    run(
+        &mut ecs,
+        |mut commands: Commands, query: Query<&Velocity>, mut moved: ResMut<Moved>| {
+            for (entity, velocity) in query.iter() {
+                commands.insert(entity, Size::new(velocity.vx as u32));
+                moved.0 += 1;
+            }
+        },
+    );
+    // Only applied at the sync point
+    assert!(ecs.get_component::<Size>().is_err());
+    ecs.apply_commands();
+    assert_eq!(ecs.get_component::<Size>().unwrap().len(), 2);
+    assert_eq!(ecs.get_resource::<Moved>().unwrap().0, 2);
+}
+
+#[test]
+fn exclusive_systems_get_the_whole_world() {
+    let mut ecs = world();
+    run(&mut ecs, |ecs: &mut ECS| {
+        ecs.get_mut_resource::<Step>().unwrap().0 = 5;
+    });
+    assert_eq!(ecs.get_resource::<Step>().unwrap().0, 5);
+}
+
+fn aliased(_positions: Query<&mut Position>, _also_positions: Query<&Position>) {}
+
+#[test]
+#[should_panic(expected = "conflicts with an earlier parameter")]
+fn queries_borrowing_the_same_component_panic() {
+    run(&mut world(), aliased);
+}
+
+fn aliased_resource(_step: ResMut<Step>, _also_step: Res<Step>) {}
+
+#[test]
+#[should_panic(expected = "conflicts with an earlier parameter")]
+fn resources_borrowed_twice_panic() {
+    run(&mut world(), aliased_resource);
+}
@@ -0,0 +1,7 @@
+use oxygin::ecs::{Component, Reflect};
+
+#[derive(Reflect, Component)]
+#[component(storage = "table")]
+struct Table;
+
+fn main() {}
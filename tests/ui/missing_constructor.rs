@@ -0,0 +1,12 @@
+use oxygin::ecs::{Component, Reflect};
+
+#[derive(Default, Reflect, Component)]
+struct Speed(u32);
+
+#[derive(Reflect, Component)]
+#[component(require(Speed = ))]
+struct Moving;
+
+fn main() {
+    let _ = Speed(0);
+}
@@ -0,0 +1,9 @@
+use oxygin::ecs::Reflect;
+
+#[derive(Reflect)]
+enum Direction {
+    Up,
+    Down,
+}
+
+fn main() {}
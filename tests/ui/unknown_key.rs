@@ -0,0 +1,7 @@
+use oxygin::ecs::{Component, Reflect};
+
+#[derive(Reflect, Component)]
+#[component(stroage = "sparse")]
+struct Typo;
+
+fn main() {}